@@ -1,28 +1,667 @@
 use dioxus::prelude::*;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+mod qr;
+#[cfg(test)]
+mod snapshot_tests;
+#[cfg(test)]
+mod tests;
 
 fn main() {
     launch(App);
 }
 
+// Identifiers are SimC's tokenized names: [a-z0-9_] segments joined by dots. Tool exports
+// sometimes carry display names instead, so identifier classes also accept any Unicode
+// word character and apostrophes ([\w']). Everything that isn't an operator, a paren or
+// whitespace stays part of a token.
 static TOKEN_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(<=|>=|<|>|=|&|\||\(|\)|[^<>=&|\(\)\s]+)").unwrap());
 
-static NOT_TALENT_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"!talent\.([a-zA-Z0-9_\.]+)").unwrap());
-static TALENT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"talent\.([a-zA-Z0-9_\.]+)").unwrap());
+static TALENT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"talent\.([\w'.]+)").unwrap());
+static DISABLED_TALENT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(!?)talent\.([\w']+)\.disabled\b").unwrap());
+
+static HUMANIZE_RULES: Lazy<Vec<(Regex, &'static str)>> = Lazy::new(|| {
+    [
+        (
+            r"^trinket\.(\d+)\.cooldown\.ready$",
+            "trinket $1 off cooldown",
+        ),
+        (
+            r"^trinket\.([\w']+)\.cooldown\.ready$",
+            "$1 trinket off cooldown",
+        ),
+        (r"^trinket\.(\d+)\.(.+)$", "trinket $1 $2"),
+        (r"^trinket\.([\w']+)\.(.+)$", "$1 trinket $2"),
+        (
+            r"^target\.time_to_pct_(\d+)$",
+            "time until target reaches $1%",
+        ),
+        (
+            r"^cooldown\.([\w']+)\.full_recharge_time$",
+            "$1 time to full charges",
+        ),
+        (
+            r"^cooldown\.([\w']+)\.charges_fractional$",
+            "$1 fractional charges",
+        ),
+        (r"^cooldown\.([\w']+)\.charges$", "$1 charges"),
+        (r"^cooldown\.([\w']+)\.duration$", "$1 cooldown length"),
+        (r"^cooldown\.([\w']+)\.remains$", "$1 cooldown remaining"),
+        (
+            r"^(rage|energy|focus|fury|mana|runic_power|astral_power)\.deficit$",
+            "$1 missing from max",
+        ),
+        (
+            r"^(rage|energy|focus|fury|mana|runic_power|astral_power)\.time_to_max$",
+            "time until $1 is full",
+        ),
+        (r"^gcd(?:\.max)?$", "GCD"),
+        (r"^gcd\.remains$", "time until GCD is ready"),
+        (r"^prev_gcd\.1\.([\w']+)$", "last GCD cast was $1"),
+        (r"^prev_gcd\.(\d+)\.([\w']+)$", "GCD cast $1 back was $2"),
+        (r"^prev_off_gcd\.([\w']+)$", "last off-GCD cast was $1"),
+        (r"^raid_event\.adds\.in$", "adds arrive in"),
+        (r"^raid_event\.adds\.remains$", "adds last for"),
+        (r"^raid_event\.movement\.in$", "forced movement in"),
+        (r"^raid_event\.stun\.exists$", "incoming stun"),
+        (
+            r"^action\.([\w']+)\.in_flight_to_target$",
+            "$1 is mid-flight to target",
+        ),
+        (r"^action\.([\w']+)\.in_flight$", "$1 is mid-flight"),
+        (
+            r"^action\.([\w']+)\.(?:execute_time|cast_time)$",
+            "$1 cast time",
+        ),
+        (r"^set_bonus\.tier(\d+)_(\d+)pc$", "tier $1 $2-piece bonus"),
+        (r"^equipped\.([\w']+)$", "$1 equipped"),
+        (r"^(main|off)_hand\.(\d)h$", "$2-handed $1-hand weapon"),
+        (r"^(main|off)_hand\.([\w']+)$", "$1-hand weapon $2"),
+        (r"^variable\.([\w']+)$", "$1"),
+        (r"^active_enemies$", "enemies"),
+        (r"^active_dot\.([\w']+)$", "targets with $1"),
+        (r"^spell_targets\.([\w']+)$", "targets hit by $1"),
+        (r"^spell_targets$", "targets in range"),
+        (r"^health\.pct$", "my HP%"),
+        (
+            r"^incoming_damage_(\d+)s$",
+            "damage taken over last $1 seconds",
+        ),
+        (r"^incoming_damage_(\d+)ms$", "damage taken over last $1 ms"),
+        (r"^crit_pct$", "crit %"),
+        (r"^haste_pct$", "haste %"),
+        (r"^mastery_value$", "mastery"),
+        (r"^movement\.distance$", "distance moved"),
+        (r"^target\.distance$", "distance to target"),
+        (r"^moving$", "while moving"),
+        (r"^(?:buff|debuff)\.([\w']+)\.max_stack$", "$1 max stacks"),
+        (
+            r"^(?:buff|debuff|dot)\.([\w']+)\.duration$",
+            "$1 total duration",
+        ),
+        (r"^(?:buff|debuff|dot)\.([\w']+)\.remains$", "$1 time left"),
+        (
+            r"^(?:buff|debuff|dot)\.([\w']+)\.tick_time$",
+            "$1 tick interval",
+        ),
+        (r"^(?:buff|debuff)\.([\w']+)\.stack_pct$", "$1 stack %"),
+        (r"^(?:buff|debuff)\.([\w']+)\.value_pct$", "$1 value %"),
+        (r"^(?:buff|debuff)\.([\w']+)\.stack$", "$1 stacks"),
+        (
+            r"^(?:buff|debuff)\.([\w']+)\.react$",
+            "$1 is up (reactable)",
+        ),
+        (r"^(?:buff|debuff)\.([\w']+)\.up$", "$1 is up"),
+        (r"^(?:buff|debuff)\.([\w']+)\.down$", "$1 is down"),
+    ]
+    .iter()
+    .map(|(pattern, replacement)| (Regex::new(pattern).unwrap(), *replacement))
+    .collect()
+});
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+struct CustomRules {
+    source: String,
+    rules: Vec<(Regex, String)>,
+    errors: Vec<String>,
+}
+
+impl PartialEq for CustomRules {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl From<String> for CustomRules {
+    fn from(source: String) -> Self {
+        let (rules, errors) = parse_custom_rules(&source);
+        CustomRules {
+            source,
+            rules,
+            errors,
+        }
+    }
+}
+
+impl From<CustomRules> for String {
+    fn from(rules: CustomRules) -> Self {
+        rules.source
+    }
+}
+
+impl CustomRules {
+    fn matches(&self, token: &str) -> bool {
+        self.rules.iter().any(|(re, _)| re.is_match(token))
+    }
+
+    fn apply(&self, token: &str) -> Option<String> {
+        self.rules
+            .iter()
+            .find(|(re, _)| re.is_match(token))
+            .map(|(re, replacement)| re.replace(token, replacement.as_str()).into_owned())
+    }
+}
+
+static EXECUTE_PHASE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"health\.pct\s*<=?\s*(20|35)\b|\bexecute(_phase)?\b").unwrap());
+static COOLDOWN_PHASE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(avatar|recklessness)\b").unwrap());
+
+static EXTERNAL_BUFF_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^buff\.([\w']+)\.up$").unwrap());
+
+static HERO_TREE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^hero_tree\.(\w+)$").unwrap());
+
+static SEASON_SET_BONUS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^set_bonus\.([a-z]+)(\d+)_(\d+)pc$").unwrap());
+static GEAR_TOKEN_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:set_bonus|equipped)\.[\w']+").unwrap());
+
+static EXTERNAL_BUFFS: &[(&str, &str)] = &[
+    ("bloodlust", "Bloodlust"),
+    ("heroism", "Heroism"),
+    ("time_warp", "Time Warp"),
+    ("primal_rage", "Primal Rage"),
+    ("fury_of_the_aspects", "Fury of the Aspects"),
+    ("drums_of_deathly_ferocity", "Drums"),
+    ("power_infusion", "Power Infusion"),
+];
+
+static KNOWN_WARRIOR_SPELLS: &[&str] = &[
+    "auto_attack",
+    "avatar",
+    "battle_shout",
+    "berserker_rage",
+    "berserker_stance",
+    "bladestorm",
+    "bloodbath",
+    "bloodthirst",
+    "champions_spear",
+    "charge",
+    "cleave",
+    "colossus_smash",
+    "crushing_blow",
+    "defensive_stance",
+    "demolish",
+    "demoralizing_shout",
+    "die_by_the_sword",
+    "enraged_regeneration",
+    "execute",
+    "hamstring",
+    "heroic_leap",
+    "heroic_strike",
+    "heroic_throw",
+    "ignore_pain",
+    "impending_victory",
+    "intervene",
+    "last_stand",
+    "mortal_strike",
+    "odyns_fury",
+    "onslaught",
+    "overpower",
+    "pummel",
+    "raging_blow",
+    "rallying_cry",
+    "rampage",
+    "ravager",
+    "recklessness",
+    "rend",
+    "revenge",
+    "shattering_throw",
+    "shield_block",
+    "shield_charge",
+    "shield_slam",
+    "shield_wall",
+    "shockwave",
+    "skullsplitter",
+    "slam",
+    "spell_reflection",
+    "storm_bolt",
+    "sweeping_strikes",
+    "thunder_blast",
+    "thunder_clap",
+    "thunderclap",
+    "thunderous_roar",
+    "victory_rush",
+    "warbreaker",
+    "whirlwind",
+    "wrecking_throw",
+    "ancestral_call",
+    "arcane_torrent",
+    "bag_of_tricks",
+    "berserking",
+    "blood_fury",
+    "fireblood",
+    "light_judgment",
+    "lights_judgment",
+    "call_action_list",
+    "run_action_list",
+    "invoke_external_buff",
+    "potion",
+    "use_item",
+    "use_items",
+    "variable",
+    "wait",
+    "wait_until",
+];
+
+static PROFILE_SETTING_KEYS: &[&str] = &[
+    "warrior",
+    "source",
+    "spec",
+    "level",
+    "race",
+    "region",
+    "server",
+    "role",
+    "position",
+    "professions",
+    "talents",
+    "class_talents",
+    "spec_talents",
+    "hero_talents",
+    "potion",
+    "flask",
+    "food",
+    "augmentation",
+    "temporary_enchant",
+    "head",
+    "neck",
+    "shoulder",
+    "shoulders",
+    "back",
+    "chest",
+    "wrist",
+    "wrists",
+    "hands",
+    "waist",
+    "legs",
+    "feet",
+    "finger1",
+    "finger2",
+    "trinket1",
+    "trinket2",
+    "main_hand",
+    "off_hand",
+];
+
+static PROFILE_HEADER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^(?i:#+\s*profile:\s*(.+)|warrior="?([^"]+)"?)$"#).unwrap());
+
+static RAGE_COSTS: &[(&str, i32)] = &[
+    ("bloodthirst", 8),
+    ("charge", 20),
+    ("cleave", -20),
+    ("execute", -20),
+    ("ignore_pain", -35),
+    ("mortal_strike", -30),
+    ("odyns_fury", 15),
+    ("raging_blow", 12),
+    ("rampage", -80),
+    ("rend", -20),
+    ("revenge", -20),
+    ("shield_block", -30),
+    ("shield_slam", 15),
+    ("skullsplitter", 15),
+    ("slam", -20),
+    ("thunder_clap", 5),
+    ("thunderclap", 5),
+    ("thunderous_roar", 10),
+    ("whirlwind", -30),
+];
+
+static PANDEMIC_REMAINS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:(?:dot|debuff|buff)\.([\w']+)\.)?remains$").unwrap());
+static PANDEMIC_DURATION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:(?:dot|debuff|buff)\.([\w']+)\.)?duration$").unwrap());
+
+static KEY_VALUE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[\w.]+=").unwrap());
+
+static STACK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(buff|debuff)\.([\w']+)\.stack$").unwrap());
+
+static TIME_TOKEN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?:gcd|gcd\.max|gcd\.remains|target\.time_to_pct_\d+|raid_event\.adds\.(?:in|remains)|raid_event\.movement\.in|.+\.(?:remains|duration|full_recharge_time|time_to_max|tick_time|execute_time|cast_time))$",
+    )
+    .unwrap()
+});
+
+const ATOM_WRAP_WIDTH: usize = 60;
+const LINE_WRAP_WIDTH: usize = 80;
+const MAX_CONDITION_DEPTH: usize = 32;
+const DEPTH_COLOR_THRESHOLD: usize = 2;
+const DEPTH_COLOR_LEVELS: usize = 5;
+const HORIZONTAL_MAX_BRANCHES: usize = 4;
+const HORIZONTAL_MAX_WIDTH: usize = 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum Theme {
+    #[default]
+    Dark,
+    HighContrast,
+    Light,
+}
+
+impl Theme {
+    const ALL: [Theme; 3] = [Theme::Dark, Theme::HighContrast, Theme::Light];
+
+    fn class(self) -> &'static str {
+        match self {
+            Theme::Dark => "theme-dark",
+            Theme::HighContrast => "theme-contrast",
+            Theme::Light => "theme-light",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::HighContrast => "High contrast",
+            Theme::Light => "Light",
+        }
+    }
+
+    fn accent(self) -> &'static str {
+        match self {
+            Theme::Dark => "#3b82f6",
+            Theme::HighContrast => "#ffd400",
+            Theme::Light => "#2563eb",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum OperatorStyle {
+    #[default]
+    Words,
+    Symbols,
+    Simc,
+}
+
+impl OperatorStyle {
+    const ALL: [OperatorStyle; 3] = [
+        OperatorStyle::Words,
+        OperatorStyle::Symbols,
+        OperatorStyle::Simc,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            OperatorStyle::Words => "words",
+            OperatorStyle::Symbols => "symbols",
+            OperatorStyle::Simc => "simc",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            OperatorStyle::Words => "AND / OR",
+            OperatorStyle::Symbols => "∧ / ∨",
+            OperatorStyle::Simc => "& / |",
+        }
+    }
+
+    fn and(self) -> &'static str {
+        match self {
+            OperatorStyle::Words => "AND",
+            OperatorStyle::Symbols => "∧",
+            OperatorStyle::Simc => "&",
+        }
+    }
+
+    fn or(self) -> &'static str {
+        match self {
+            OperatorStyle::Words => "OR",
+            OperatorStyle::Symbols => "∨",
+            OperatorStyle::Simc => "|",
+        }
+    }
+
+    fn not_prefix(self) -> &'static str {
+        match self {
+            OperatorStyle::Words => "NOT ",
+            OperatorStyle::Symbols => "¬",
+            OperatorStyle::Simc => "!",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum Indentation {
+    TwoSpaces,
+    #[default]
+    FourSpaces,
+    Tab,
+}
+
+impl Indentation {
+    const ALL: [Indentation; 3] = [
+        Indentation::TwoSpaces,
+        Indentation::FourSpaces,
+        Indentation::Tab,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            Indentation::TwoSpaces => "2",
+            Indentation::FourSpaces => "4",
+            Indentation::Tab => "tab",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Indentation::TwoSpaces => "2 spaces",
+            Indentation::FourSpaces => "4 spaces",
+            Indentation::Tab => "Tab",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Indentation::TwoSpaces => "  ",
+            Indentation::FourSpaces => "    ",
+            Indentation::Tab => "\t",
+        }
+    }
+
+    // Card views indent with CSS; a tab is as wide as the output's tab-size.
+    fn css_width(self) -> &'static str {
+        match self {
+            Indentation::TwoSpaces => "2ch",
+            Indentation::FourSpaces | Indentation::Tab => "4ch",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+enum CommentMode {
+    Ignore,
+    #[default]
+    Annotate,
+    Inline,
+}
+
+impl CommentMode {
+    const ALL: [CommentMode; 3] = [
+        CommentMode::Ignore,
+        CommentMode::Annotate,
+        CommentMode::Inline,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            CommentMode::Ignore => "ignore",
+            CommentMode::Annotate => "annotate",
+            CommentMode::Inline => "inline",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            CommentMode::Ignore => "Ignore",
+            CommentMode::Annotate => "As annotations",
+            CommentMode::Inline => "Inline",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    theme: Theme,
+    operator_style: OperatorStyle,
+    comment_mode: CommentMode,
+    indentation: Indentation,
+    humanize: bool,
+    normalize_whitespace: bool,
+    collapse_long_or: bool,
+    or_collapse_threshold: usize,
+    highlight_phases: bool,
+    tree_view: bool,
+    group_by_spell: bool,
+    validate_spells: bool,
+    effective_conditions: bool,
+    ladder_view: bool,
+    collapse_long_conditions: bool,
+    condition_line_limit: usize,
+    merge_shared_conditions: bool,
+    simplify_conditions: bool,
+    show_rage_costs: bool,
+    wrap_at_operators: bool,
+    continuous_numbering: bool,
+    compare_reference: Option<String>,
+    lint_unreachable: bool,
+    horizontal_layout: bool,
+    depth_colors: bool,
+    custom_rules: CustomRules,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: Theme::Dark,
+            operator_style: OperatorStyle::Words,
+            comment_mode: CommentMode::Annotate,
+            indentation: Indentation::FourSpaces,
+            humanize: false,
+            normalize_whitespace: false,
+            collapse_long_or: false,
+            or_collapse_threshold: 5,
+            highlight_phases: false,
+            tree_view: false,
+            group_by_spell: false,
+            validate_spells: false,
+            effective_conditions: false,
+            ladder_view: false,
+            collapse_long_conditions: false,
+            condition_line_limit: 8,
+            merge_shared_conditions: false,
+            simplify_conditions: false,
+            show_rage_costs: false,
+            wrap_at_operators: false,
+            continuous_numbering: false,
+            compare_reference: None,
+            lint_unreachable: false,
+            horizontal_layout: false,
+            depth_colors: false,
+            custom_rules: CustomRules::default(),
+        }
+    }
+}
+
+impl Settings {
+    fn or_collapse_over(&self) -> Option<usize> {
+        self.collapse_long_or.then_some(self.or_collapse_threshold)
+    }
+
+    fn condition_lines_over(&self) -> Option<usize> {
+        self.collapse_long_conditions
+            .then_some(self.condition_line_limit)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Action {
+    spell: String,
+    condition: Option<Expr>,
+    interrupt: Option<Expr>,
+    chain: bool,
+    raw_condition: Option<String>,
+    raw_interrupt: Option<String>,
+    annotation: Option<String>,
+    comment: bool,
+    error: Option<String>,
+}
+
+impl Action {
+    fn comment(text: &str) -> Self {
+        Action {
+            spell: String::new(),
+            condition: None,
+            interrupt: None,
+            chain: false,
+            raw_condition: None,
+            raw_interrupt: None,
+            annotation: Some(text.to_string()),
+            comment: true,
+            error: None,
+        }
+    }
+}
 
 fn tokenize_line(line: &str) -> Vec<&str> {
     TOKEN_RE.find_iter(line).map(|m| m.as_str()).collect()
 }
 
-#[derive(Debug)]
+fn logical_tokens(line: &str) -> Vec<String> {
+    tokenize_line(line)
+        .into_iter()
+        .flat_map(|token| match token {
+            "&" => vec!["and"],
+            "|" => vec!["or"],
+            "!" => vec!["not"],
+            other => match other.strip_prefix('!') {
+                Some(rest) => vec!["not", rest],
+                None => vec![other],
+            },
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
 enum Expr {
-    Atom(String),
+    Atom(Vec<String>),
     And(Vec<Expr>),
     Or(Vec<Expr>),
+    Not(Box<Expr>),
 }
 
 fn split_top_level<'a>(tokens: &'a [&'a str], op: &str) -> Option<Vec<&'a [&'a str]>> {
@@ -77,25 +716,72 @@ fn parse_expr(tokens: &[&str]) -> Expr {
         }
     }
 
-    Expr::Atom(tokens.join(" "))
+    if tokens.len() > 1 && tokens[0] == "not" {
+        return Expr::Not(Box::new(parse_expr(&tokens[1..])));
+    }
+
+    Expr::Atom(tokens.iter().map(|tok| tok.to_string()).collect())
 }
 
-fn pretty_format_condition(expr: &Expr, indent: usize) -> String {
-    let indent_str = "    ".repeat(indent);
+fn wrap_atom(atom: &str, indent_str: &str, unit: &str) -> String {
+    let width = ATOM_WRAP_WIDTH.saturating_sub(indent_str.chars().count() + unit.chars().count());
+    if atom.chars().count() <= width {
+        return format!("{}{}", indent_str, atom);
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for piece in atom.split_inclusive(['+', '*', '/', '%']) {
+        if !line.trim().is_empty() && line.chars().count() + piece.chars().count() > width {
+            lines.push(line.trim().to_string());
+            line.clear();
+        }
+        line.push_str(piece);
+    }
+    if !line.trim().is_empty() {
+        lines.push(line.trim().to_string());
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            if i == 0 {
+                format!("{}{}", indent_str, l)
+            } else {
+                format!("{}{}{}", indent_str, unit, l)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
+fn unwrap_single_group(expr: &Expr) -> &Expr {
     match expr {
-        Expr::Atom(s) => format!("{}{}", indent_str, s),
+        Expr::And(parts) | Expr::Or(parts) if parts.len() == 1 => unwrap_single_group(&parts[0]),
+        _ => expr,
+    }
+}
+
+fn pretty_format_condition(
+    expr: &Expr,
+    indent: usize,
+    style: OperatorStyle,
+    indentation: Indentation,
+) -> String {
+    let indent_str = indentation.unit().repeat(indent);
+
+    match unwrap_single_group(expr) {
+        Expr::Atom(tokens) => wrap_atom(&tokens.join(" "), &indent_str, indentation.unit()),
         Expr::And(parts) => {
             if parts.is_empty() {
                 return "".to_string();
             }
-            if parts.len() == 1 {
-                return pretty_format_condition(&parts[0], indent);
-            }
 
             let formatted_parts: Vec<String> = parts
                 .iter()
                 .map(|part| {
+                    let part = unwrap_single_group(part);
                     let is_multiline_or = if let Expr::Or(sub_parts) = part {
                         sub_parts.len() > 1
                     } else {
@@ -103,157 +789,3263 @@ fn pretty_format_condition(expr: &Expr, indent: usize) -> String {
                     };
 
                     if is_multiline_or {
-                        let or_content = pretty_format_condition(part, indent + 1);
+                        let or_content =
+                            pretty_format_condition(part, indent + 1, style, indentation);
                         format!("(\n{}\n{})", or_content, indent_str)
+                    } else if let Expr::Atom(tokens) = part {
+                        tokens.join(" ")
                     } else {
-                        let formatted = pretty_format_condition(part, 0);
-                        format!("{}", formatted.trim())
+                        let formatted = pretty_format_condition(part, indent, style, indentation);
+                        formatted.trim().to_string()
                     }
                 })
                 .collect();
 
-            format!("{}{}", indent_str, formatted_parts.join(" AND "))
+            let joiner = format!(" {} ", style.and());
+            let mut out = indent_str.clone();
+            for (i, part) in formatted_parts.iter().enumerate() {
+                let column = out.rsplit('\n').next().unwrap_or("").chars().count();
+                let first_line = part.lines().next().unwrap_or("");
+                if i == 0 {
+                    out.push_str(part);
+                } else if !part.contains('\n')
+                    && column + joiner.chars().count() + first_line.chars().count()
+                        > LINE_WRAP_WIDTH
+                {
+                    out.push_str(&format!(
+                        "\n{}{}{} {}",
+                        indent_str,
+                        indentation.unit(),
+                        style.and(),
+                        part
+                    ));
+                } else {
+                    out.push_str(&joiner);
+                    out.push_str(part);
+                }
+            }
+            out
         }
         Expr::Or(parts) => {
             if parts.is_empty() {
                 return "".to_string();
             }
-            if parts.len() == 1 {
-                return pretty_format_condition(&parts[0], indent);
-            }
 
             let formatted_parts: Vec<String> = parts
                 .iter()
                 .enumerate()
                 .map(|(i, part)| {
                     if i == 0 {
-                        pretty_format_condition(part, indent)
+                        pretty_format_condition(part, indent, style, indentation)
                     } else {
-                        let formatted = pretty_format_condition(part, 0);
-                        format!("{}OR {}", indent_str, formatted.trim())
+                        let formatted = pretty_format_condition(part, indent, style, indentation);
+                        format!("{}{} {}", indent_str, style.or(), formatted.trim())
                     }
                 })
                 .collect();
 
             formatted_parts.join("\n")
         }
+        Expr::Not(inner) => match unwrap_single_group(inner) {
+            Expr::Atom(_) | Expr::Not(_) => format!(
+                "{}{}{}",
+                indent_str,
+                style.not_prefix(),
+                pretty_format_condition(inner, indent, style, indentation).trim()
+            ),
+            _ => format!(
+                "{}{}(\n{}\n{})",
+                indent_str,
+                style.not_prefix(),
+                pretty_format_condition(inner, indent + 1, style, indentation),
+                indent_str
+            ),
+        },
     }
 }
 
-fn transform_condition(raw: &str) -> String {
-    let result = NOT_TALENT_RE.replace_all(raw, "$1 not talented");
-    let result = TALENT_RE.replace_all(&result, "$1 talented");
+struct TokenRule {
+    examples: fn() -> Vec<String>,
+    humanize: fn(&str) -> Option<String>,
+}
 
-    let tokens = tokenize_line(&result);
-    let transformed_tokens: Vec<String> = tokens
-        .iter()
-        .map(|token| match *token {
-            "&" => "and".to_string(),
-            "|" => "or".to_string(),
-            "!" => "not".to_string(),
-            other => other.replace("debuff.", "").replace("buff.", ""),
-        })
-        .collect();
+static TOKEN_RULES: &[TokenRule] = &[
+    TokenRule {
+        examples: || {
+            EXTERNAL_BUFFS
+                .iter()
+                .map(|(id, _)| format!("buff.{}.up", id))
+                .collect()
+        },
+        humanize: humanize_external_buff,
+    },
+    TokenRule {
+        examples: || vec!["set_bonus.tww2_4pc".to_string()],
+        humanize: humanize_season_set_bonus,
+    },
+    TokenRule {
+        examples: || vec!["hero_tree.slayer".to_string()],
+        humanize: humanize_hero_tree,
+    },
+];
 
-    let expr = parse_expr(
-        &transformed_tokens
-            .iter()
-            .map(|s| s.as_str())
-            .collect::<Vec<_>>(),
-    );
+fn humanize_external_buff(token: &str) -> Option<String> {
+    let caps = EXTERNAL_BUFF_RE.captures(token)?;
+    let (_, name) = EXTERNAL_BUFFS.iter().find(|(id, _)| *id == &caps[1])?;
+    Some(format!("{} active (raid buff)", name))
+}
 
-    pretty_format_condition(&expr, 1)
+fn humanize_season_set_bonus(token: &str) -> Option<String> {
+    let caps = SEASON_SET_BONUS_RE
+        .captures(token)
+        .filter(|caps| &caps[1] != "tier")?;
+    Some(format!(
+        "{} season {} {}-piece bonus",
+        caps[1].to_uppercase(),
+        &caps[2],
+        &caps[3]
+    ))
 }
 
-fn process_line(line: &str) -> Option<(String, String)> {
-    let (when_raw, spell_raw) = if let Some((a, b)) = line.split_once("+=/") {
-        (a.trim(), b.trim())
-    } else if let Some((a, b)) = line.split_once('=') {
-        (a.trim(), b.trim())
-    } else {
-        return None;
+fn humanize_hero_tree(token: &str) -> Option<String> {
+    let caps = HERO_TREE_RE.captures(token)?;
+    Some(format!("{} hero tree", title_case(&caps[1])))
+}
+
+fn humanize_token(token: &str) -> Option<String> {
+    if let Some(humanized) = TOKEN_RULES.iter().find_map(|rule| (rule.humanize)(token)) {
+        return Some(humanized);
+    }
+
+    HUMANIZE_RULES
+        .iter()
+        .find(|(re, _)| re.is_match(token))
+        .map(|(re, replacement)| re.replace(token, *replacement).replace('.', " "))
+}
+
+fn readable_pattern(pattern: &str) -> (String, Vec<String>) {
+    let pattern = pattern.trim_start_matches('^').trim_end_matches('$');
+    let mut display = String::new();
+    let mut placeholders = Vec::new();
+    let mut chars = pattern.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    display.push(escaped);
+                }
+            }
+            '(' if !pattern[start + 1..].starts_with('?') => {
+                let mut depth = 1;
+                let mut end = start + 1;
+                for (i, c) in chars.by_ref() {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        end = i;
+                        break;
+                    }
+                }
+                let (shown, example) = match &pattern[start + 1..end] {
+                    r"[\w']+" | r"\w+" => ("X".to_string(), "X"),
+                    r"\d+" | r"\d" => ("N".to_string(), "N"),
+                    ".+" => ("…".to_string(), "…"),
+                    alternatives => (
+                        format!("({})", alternatives),
+                        alternatives.split('|').next().unwrap_or(alternatives),
+                    ),
+                };
+                display.push_str(&shown);
+                placeholders.push(example.to_string());
+            }
+            '(' => {
+                chars.next();
+                chars.next();
+                display.push('(');
+            }
+            _ => display.push(c),
+        }
+    }
+    (display, placeholders)
+}
+
+fn glossary_entries() -> Vec<(String, String)> {
+    let mut entries = BTreeMap::new();
+    for (re, replacement) in HUMANIZE_RULES.iter() {
+        let (pattern, placeholders) = readable_pattern(re.as_str());
+        let mut rendering = replacement.to_string();
+        for (i, placeholder) in placeholders.iter().enumerate().rev() {
+            rendering = rendering.replace(&format!("${}", i + 1), placeholder);
+        }
+        entries
+            .entry(pattern)
+            .or_insert_with(|| rendering.replace('.', " "));
+    }
+    for rule in TOKEN_RULES {
+        for example in (rule.examples)() {
+            if let Some(rendering) = (rule.humanize)(&example) {
+                entries.insert(example, rendering);
+            }
+        }
+    }
+    let settings = Settings {
+        humanize: true,
+        ..Settings::default()
     };
+    for rule in ATOM_RULES {
+        let Expr::Atom(tokens) = parse_simc_condition(rule.example) else {
+            continue;
+        };
+        let tokens: Vec<&str> = tokens.iter().map(String::as_str).collect();
+        if let Some(rendering) = (rule.humanize)(&tokens, &settings) {
+            entries.insert(tokens.join(" "), rendering.join(" "));
+        }
+    }
+    entries.into_iter().collect()
+}
 
-    let when = when_raw
-        .strip_prefix("actions.")
-        .unwrap_or(when_raw)
-        .to_string();
+fn is_arithmetic(c: char) -> bool {
+    matches!(c, '*' | '/' | '+' | '-' | '%')
+}
+
+fn tree_lines(
+    expr: &Expr,
+    style: OperatorStyle,
+    first_prefix: &str,
+    child_prefix: &str,
+    out: &mut Vec<String>,
+) {
+    match unwrap_single_group(expr) {
+        Expr::Atom(tokens) => out.push(format!("{}{}", first_prefix, tokens.join(" "))),
+        Expr::Not(inner) => {
+            out.push(format!("{}{}", first_prefix, style.not_prefix().trim()));
+            tree_lines(
+                inner,
+                style,
+                &format!("{}└─ ", child_prefix),
+                &format!("{}   ", child_prefix),
+                out,
+            );
+        }
+        Expr::And(parts) | Expr::Or(parts) => {
+            let label = if matches!(unwrap_single_group(expr), Expr::And(_)) {
+                style.and()
+            } else {
+                style.or()
+            };
+            out.push(format!("{}{}", first_prefix, label));
+            for (i, part) in parts.iter().enumerate() {
+                let (branch, continuation) = if i + 1 == parts.len() {
+                    ("└─ ", "   ")
+                } else {
+                    ("├─ ", "│  ")
+                };
+                tree_lines(
+                    part,
+                    style,
+                    &format!("{}{}", child_prefix, branch),
+                    &format!("{}{}", child_prefix, continuation),
+                    out,
+                );
+            }
+        }
+    }
+}
 
-    let (spell, condition_opt) = if let Some((s, cond)) = spell_raw.split_once(",if=") {
-        (s.trim(), Some(cond.trim()))
+fn dot_nodes(expr: &Expr, next_id: &mut usize, out: &mut Vec<String>) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    let (label, children): (String, Vec<&Expr>) = match unwrap_single_group(expr) {
+        Expr::Atom(tokens) => (tokens.join(" "), Vec::new()),
+        Expr::And(parts) => ("AND".to_string(), parts.iter().collect()),
+        Expr::Or(parts) => ("OR".to_string(), parts.iter().collect()),
+        Expr::Not(inner) => ("NOT".to_string(), vec![inner.as_ref()]),
+    };
+    let shape = if children.is_empty() {
+        "box"
     } else {
-        (spell_raw.trim(), None)
+        "ellipse"
     };
+    out.push(format!(
+        "  n{} [label=\"{}\", shape={}];",
+        id,
+        label.replace('\\', "\\\\").replace('"', "\\\""),
+        shape
+    ));
+    for child in children {
+        let child_id = dot_nodes(child, next_id, out);
+        out.push(format!("  n{} -> n{};", id, child_id));
+    }
+    id
+}
+
+fn expr_to_dot(expr: &Expr) -> String {
+    let mut lines = vec!["digraph condition {".to_string()];
+    dot_nodes(expr, &mut 0, &mut lines);
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+fn format_condition_tree(expr: &Expr, style: OperatorStyle) -> String {
+    let mut lines = Vec::new();
+    tree_lines(expr, style, "", "", &mut lines);
+    lines.join("\n")
+}
+
+fn transform_condition(raw: &str, settings: &Settings) -> Expr {
+    let raw = DISABLED_TALENT_RE.replace_all(raw, |caps: &regex::Captures| {
+        let m = caps.get(0).unwrap();
+        if raw[m.end()..].chars().next().is_some_and(is_arithmetic) {
+            m.as_str().to_string()
+        } else if caps[1].is_empty() {
+            format!("!talent.{}", &caps[2])
+        } else {
+            format!("talent.{}", &caps[2])
+        }
+    });
+    let result = TALENT_RE.replace_all(&raw, |caps: &regex::Captures| {
+        let m = caps.get(0).unwrap();
+        let before = raw[..m.start()].chars().next_back();
+        let after = raw[m.end()..].chars().next();
+        if before.is_some_and(is_arithmetic) || after.is_some_and(is_arithmetic) {
+            m.as_str().to_string()
+        } else {
+            let talent = caps[1].strip_suffix(".enabled").unwrap_or(&caps[1]);
+            format!("{} talented", talent)
+        }
+    });
+
+    let tokens: Vec<String> = logical_tokens(&result)
+        .into_iter()
+        .map(|token| {
+            TALENT_RE
+                .replace_all(&token, |caps: &regex::Captures| {
+                    let talent = caps[1].strip_suffix(".enabled").unwrap_or(&caps[1]);
+                    format!("{} (talent rank)", talent)
+                })
+                .into_owned()
+        })
+        .collect();
 
-    let result = if let Some(cond_str) = condition_opt {
-        let formatted_condition = transform_condition(cond_str);
-        format!("{}:\n{}", spell, formatted_condition)
+    let expr = cancel_double_negation(parse_expr(
+        &tokens.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+    ));
+    let expr = if settings.simplify_conditions {
+        simplify(expr)
     } else {
-        spell.to_string()
+        expr
     };
+    let expr = map_atoms(expr, &|tokens| transform_atom(tokens, settings));
+    if settings.humanize {
+        fold_negated_phrases(expr)
+    } else {
+        expr
+    }
+}
 
-    Some((when, result))
+fn cancel_double_negation(expr: Expr) -> Expr {
+    match expr {
+        Expr::Not(inner) => match unwrap_single_group(&inner).clone() {
+            Expr::Not(twice) => cancel_double_negation(*twice),
+            inner => Expr::Not(Box::new(cancel_double_negation(inner))),
+        },
+        Expr::And(parts) => Expr::And(parts.into_iter().map(cancel_double_negation).collect()),
+        Expr::Or(parts) => Expr::Or(parts.into_iter().map(cancel_double_negation).collect()),
+        atom => atom,
+    }
 }
 
-fn process_apl_grouped(apl: &str) -> BTreeMap<String, Vec<String>> {
-    let mut groups = BTreeMap::new();
+fn fold_negated_phrases(expr: Expr) -> Expr {
+    match expr {
+        Expr::Not(inner) => match *inner {
+            Expr::Atom(tokens) if tokens == ["while moving"] => {
+                Expr::Atom(vec!["while not moving".to_string()])
+            }
+            inner => Expr::Not(Box::new(fold_negated_phrases(inner))),
+        },
+        Expr::And(parts) => Expr::And(parts.into_iter().map(fold_negated_phrases).collect()),
+        Expr::Or(parts) => Expr::Or(parts.into_iter().map(fold_negated_phrases).collect()),
+        atom => atom,
+    }
+}
 
-    for line in apl.lines() {
-        let trimmed_line = line.trim();
-        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
-            continue;
-        }
+/// Purely syntactic boolean simplification: removes duplicate operands (`a&a` → `a`) and
+/// absorbed ones (`a|(a&b)` → `a`, `a&(a|b)` → `a`). Operands only count as equal when
+/// their token sequences match exactly; no arithmetic or comparison semantics are used.
+fn simplify(expr: Expr) -> Expr {
+    let is_and = matches!(expr, Expr::And(_));
+    let parts = match expr {
+        Expr::Atom(_) => return expr,
+        Expr::Not(inner) => return Expr::Not(Box::new(simplify(*inner))),
+        Expr::And(parts) | Expr::Or(parts) => parts,
+    };
 
-        if let Some((when, spell_and_condition)) = process_line(trimmed_line) {
-            groups
-                .entry(when)
-                .or_insert_with(Vec::new)
-                .push(spell_and_condition);
+    let mut flat: Vec<Expr> = Vec::new();
+    for part in parts {
+        let part = simplify(part);
+        let nested = match part {
+            Expr::And(inner) if is_and => inner,
+            Expr::Or(inner) if !is_and => inner,
+            other => vec![other],
+        };
+        for part in nested {
+            if !flat.contains(&part) {
+                flat.push(part);
+            }
         }
     }
-    groups
+
+    let operands = |part: &Expr| match part {
+        Expr::Or(inner) if is_and => inner.clone(),
+        Expr::And(inner) if !is_and => inner.clone(),
+        other => vec![other.clone()],
+    };
+    let absorbed = |index: usize| {
+        let own = operands(&flat[index]);
+        flat.iter().enumerate().any(|(i, other)| {
+            let theirs = operands(other);
+            i != index
+                && theirs.iter().all(|operand| own.contains(operand))
+                && (theirs.len() < own.len() || i < index)
+        })
+    };
+    let kept: Vec<Expr> = (0..flat.len())
+        .filter(|&i| !absorbed(i))
+        .map(|i| flat[i].clone())
+        .collect();
+
+    match kept.len() {
+        1 => kept.into_iter().next().unwrap(),
+        _ if is_and => Expr::And(kept),
+        _ => Expr::Or(kept),
+    }
 }
 
-#[component]
-fn App() -> Element {
-    let mut input = use_signal(|| "".to_string());
-    let groups = process_apl_grouped(&input());
+fn map_atoms(expr: Expr, f: &impl Fn(Vec<String>) -> Vec<String>) -> Expr {
+    match expr {
+        Expr::Atom(tokens) => Expr::Atom(f(tokens)),
+        Expr::And(parts) => Expr::And(parts.into_iter().map(|p| map_atoms(p, f)).collect()),
+        Expr::Or(parts) => Expr::Or(parts.into_iter().map(|p| map_atoms(p, f)).collect()),
+        Expr::Not(inner) => Expr::Not(Box::new(map_atoms(*inner, f))),
+    }
+}
 
-    rsx! {
-        head {
-            style {
-                {r#"
-                html, body {
-                    margin: 0;
-                    padding: 0;
-                    background-color: #05080d;
-                    color: #f3f4f6;
-                    height: 100%;
-                    font-family: "Inter", -apple-system, BlinkMacSystemFont, "Segoe UI", "Roboto", sans-serif;
-                }
+fn transform_atom(tokens: Vec<String>, settings: &Settings) -> Vec<String> {
+    let overridden = |token: &String| settings.custom_rules.matches(token);
+    if settings.humanize && !tokens.iter().any(overridden) {
+        if let Some(humanized) = humanize_atom(&tokens, settings) {
+            return humanized;
+        }
+    }
 
-                * {
-                    box-sizing: border-box;
-                }
+    tokens
+        .iter()
+        .map(|token| transform_token(token, settings))
+        .collect()
+}
 
-                .app-container {
-                    display: flex;
-                    flex-direction: column;
-                    gap: 1.5rem;
-                    max-width: 1200px;
-                    padding: 1.5rem;
-                    margin: 0 auto;
-                    background-color: #05080d;
-                    color: #f3f4f6;
-                    min-height: 100vh;
-                }
+fn transform_token(token: &str, settings: &Settings) -> String {
+    settings
+        .humanize
+        .then(|| {
+            settings
+                .custom_rules
+                .apply(token)
+                .or_else(|| humanize_token(token))
+        })
+        .flatten()
+        .unwrap_or_else(|| token.replace("debuff.", "").replace("buff.", ""))
+}
+
+fn parse_custom_rules(text: &str) -> (Vec<(Regex, String)>, Vec<String>) {
+    let mut rules = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((pattern, replacement)) = line.split_once("=>") else {
+            errors.push(format!("line {}: expected `pattern => replacement`", i + 1));
+            continue;
+        };
+        match Regex::new(&format!("^(?:{})$", pattern.trim())) {
+            Ok(re) => rules.push((re, replacement.trim().to_string())),
+            Err(e) => errors.push(format!("line {}: {}", i + 1, e)),
+        }
+    }
+    (rules, errors)
+}
+
+fn is_count_operand(token: &str) -> bool {
+    token.parse::<f64>().is_ok() || token.starts_with("variable.")
+}
+
+fn stack_comparison_phrase(op: &str) -> Option<&'static str> {
+    match op {
+        "=" => Some("at"),
+        ">=" => Some("at least"),
+        "<=" => Some("at most"),
+        ">" => Some("more than"),
+        "<" => Some("fewer than"),
+        _ => None,
+    }
+}
+
+fn comparison_phrase(op: &str) -> Option<&'static str> {
+    match op {
+        "=" => Some("exactly"),
+        ">=" => Some("at least"),
+        "<=" => Some("at most"),
+        ">" => Some("more than"),
+        "<" => Some("less than"),
+        _ => None,
+    }
+}
+
+fn split_arithmetic(operand: &str) -> (&str, &str) {
+    match operand.find(is_arithmetic) {
+        Some(pos) if pos > 0 => operand.split_at(pos),
+        _ => (operand, ""),
+    }
+}
+
+fn is_time_operand(operand: &str) -> bool {
+    TIME_TOKEN_RE.is_match(split_arithmetic(operand).0)
+}
+
+fn humanize_operand(operand: &str, settings: &Settings) -> String {
+    let (head, tail) = split_arithmetic(operand);
+    let head = transform_token(head, settings);
+    if tail.is_empty() {
+        return head;
+    }
+    let tail = tail
+        .chars()
+        .map(|c| {
+            if is_arithmetic(c) {
+                format!(" {} ", c)
+            } else {
+                c.to_string()
+            }
+        })
+        .collect::<String>();
+    format!("{}{}", head, tail)
+}
+
+fn time_operand_phrase(operand: &str, other: &str, settings: &Settings) -> String {
+    let seconds = operand.parse::<f64>();
+    if seconds.is_ok() && is_time_operand(other) {
+        let unit = if seconds == Ok(1.0) {
+            "second"
+        } else {
+            "seconds"
+        };
+        format!("{} {}", operand, unit)
+    } else {
+        humanize_operand(operand, settings)
+    }
+}
+
+type AtomHandler = fn(&[&str], &Settings) -> Option<Vec<String>>;
+
+struct AtomRule {
+    example: &'static str,
+    humanize: AtomHandler,
+}
+
+static ATOM_RULES: &[AtomRule] = &[
+    AtomRule {
+        example: "moving=0",
+        humanize: humanize_not_moving,
+    },
+    AtomRule {
+        example: "moving=1",
+        humanize: humanize_moving,
+    },
+    AtomRule {
+        example: "buff.enrage.stack>=buff.enrage.max_stack",
+        humanize: humanize_max_stacks,
+    },
+    AtomRule {
+        example: "buff.enrage.stack<3",
+        humanize: humanize_stack_count,
+    },
+    AtomRule {
+        example: "active_dot.deep_wounds<active_enemies",
+        humanize: humanize_missing_dot,
+    },
+    AtomRule {
+        example: "active_enemies>=3",
+        humanize: humanize_target_count,
+    },
+    AtomRule {
+        example: "target.distance>8",
+        humanize: humanize_distance,
+    },
+    AtomRule {
+        example: "dot.rend.remains<duration*0.3",
+        humanize: humanize_pandemic,
+    },
+    AtomRule {
+        example: "cooldown.avatar.remains<8",
+        humanize: humanize_time_comparison,
+    },
+];
+
+fn humanize_atom(tokens: &[String], settings: &Settings) -> Option<Vec<String>> {
+    let tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+    ATOM_RULES
+        .iter()
+        .find_map(|rule| (rule.humanize)(&tokens, settings))
+}
+
+fn humanize_not_moving(tokens: &[&str], _: &Settings) -> Option<Vec<String>> {
+    matches!(tokens, ["moving", "=", "0"] | ["moving", "<", "1"])
+        .then(|| vec!["while not moving".to_string()])
+}
+
+fn humanize_moving(tokens: &[&str], _: &Settings) -> Option<Vec<String>> {
+    matches!(tokens, ["moving", "=", "1"] | ["moving", ">", "0"])
+        .then(|| vec!["while moving".to_string()])
+}
+
+fn humanize_max_stacks(tokens: &[&str], _: &Settings) -> Option<Vec<String>> {
+    let [lhs, ">=" | "=", rhs] = tokens else {
+        return None;
+    };
+    if rhs.strip_suffix("max_stack") != lhs.strip_suffix("stack") {
+        return None;
+    }
+    let caps = STACK_RE.captures(lhs)?;
+    let on_target = if &caps[1] == "debuff" {
+        " on target"
+    } else {
+        ""
+    };
+    Some(vec![format!("{} at max stacks{}", &caps[2], on_target)])
+}
+
+fn humanize_stack_count(tokens: &[&str], settings: &Settings) -> Option<Vec<String>> {
+    let [lhs, op, rhs] = tokens else {
+        return None;
+    };
+    let caps = STACK_RE.captures(lhs).filter(|_| is_count_operand(rhs))?;
+    let phrase = stack_comparison_phrase(op)?;
+    let on_target = if &caps[1] == "debuff" {
+        " on target"
+    } else {
+        ""
+    };
+    Some(vec![format!(
+        "{} {} {} stacks{}",
+        &caps[2],
+        phrase,
+        humanize_operand(rhs, settings),
+        on_target
+    )])
+}
+
+fn humanize_missing_dot(tokens: &[&str], _: &Settings) -> Option<Vec<String>> {
+    let [lhs, "<", "active_enemies" | "spell_targets"] = tokens else {
+        return None;
+    };
+    let dot = lhs.strip_prefix("active_dot.")?;
+    Some(vec![format!(
+        "{} not on every target (apply {} to more targets)",
+        dot, dot
+    )])
+}
+
+fn humanize_target_count(tokens: &[&str], settings: &Settings) -> Option<Vec<String>> {
+    let [lhs, op, rhs] = tokens else {
+        return None;
+    };
+    if !is_count_operand(rhs) {
+        return None;
+    }
+    let (prefix, mut noun) = target_count_noun(lhs)?;
+    let phrase = match *op {
+        "=" => "exactly",
+        op => stack_comparison_phrase(op)?,
+    };
+    if *rhs == "1" {
+        noun = noun
+            .replacen("enemies", "enemy", 1)
+            .replacen("targets", "target", 1);
+    }
+    Some(vec![format!(
+        "{}{} {} {}",
+        prefix,
+        phrase,
+        humanize_operand(rhs, settings),
+        noun
+    )])
+}
+
+fn humanize_distance(tokens: &[&str], settings: &Settings) -> Option<Vec<String>> {
+    let ["target.distance", op, rhs] = tokens else {
+        return None;
+    };
+    if !is_count_operand(rhs) {
+        return None;
+    }
+    Some(vec![format!(
+        "distance to target {} {}",
+        comparison_phrase(op)?,
+        transform_token(rhs, settings)
+    )])
+}
+
+fn humanize_pandemic(tokens: &[&str], settings: &Settings) -> Option<Vec<String>> {
+    let [lhs, op @ ("<" | "<="), rhs] = tokens else {
+        return None;
+    };
+    let duration = rhs
+        .strip_suffix("*0.3")
+        .or_else(|| rhs.strip_prefix("0.3*"))?;
+    let remains = PANDEMIC_REMAINS_RE.captures(lhs)?;
+    let total = PANDEMIC_DURATION_RE.captures(duration)?;
+    if remains
+        .get(1)
+        .zip(total.get(1))
+        .is_some_and(|(a, b)| a.as_str() != b.as_str())
+    {
+        return None;
+    }
+    let duration = if total.get(1).is_some() {
+        transform_token(duration, settings)
+    } else {
+        "its duration".to_string()
+    };
+    Some(vec![format!(
+        "{} {} 30% of {} (pandemic refresh window)",
+        transform_token(lhs, settings),
+        comparison_phrase(op)?,
+        duration
+    )])
+}
+
+fn humanize_time_comparison(tokens: &[&str], settings: &Settings) -> Option<Vec<String>> {
+    let [lhs, op, rhs] = tokens else {
+        return None;
+    };
+    if !is_time_operand(lhs) && !is_time_operand(rhs) {
+        return None;
+    }
+    Some(vec![format!(
+        "{} {} {}",
+        time_operand_phrase(lhs, rhs, settings),
+        comparison_phrase(op)?,
+        time_operand_phrase(rhs, lhs, settings)
+    )])
+}
+
+fn target_count_noun(token: &str) -> Option<(String, String)> {
+    if token == "active_enemies" {
+        Some((String::new(), "enemies".to_string()))
+    } else if token == "spell_targets" {
+        Some((String::new(), "targets in range".to_string()))
+    } else if let Some(spell) = token.strip_prefix("spell_targets.") {
+        Some((String::new(), format!("targets hit by {}", spell)))
+    } else {
+        let dot = token.strip_prefix("active_dot.")?;
+        Some((format!("{} on ", dot), "targets".to_string()))
+    }
+}
+
+fn title_case(identifier: &str) -> String {
+    identifier
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn prose_atom(tokens: &[String]) -> String {
+    tokens
+        .iter()
+        .map(|token| match token.as_str() {
+            "<" => "is less than",
+            ">" => "is more than",
+            "<=" => "is at most",
+            ">=" => "is at least",
+            "=" => "is",
+            other => other,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn prose_condition(expr: &Expr) -> String {
+    match unwrap_single_group(expr) {
+        Expr::Atom(tokens) => prose_atom(tokens),
+        Expr::And(parts) => parts
+            .iter()
+            .map(|part| match unwrap_single_group(part) {
+                Expr::Or(_) => format!("either {}", prose_condition(part)),
+                _ => prose_condition(part),
+            })
+            .collect::<Vec<_>>()
+            .join(" and "),
+        Expr::Or(parts) => parts
+            .iter()
+            .map(prose_condition)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        Expr::Not(inner) => match unwrap_single_group(inner) {
+            Expr::Atom(tokens) if tokens.last().is_some_and(|t| t == "talented") => {
+                format!("{} not talented", tokens[..tokens.len() - 1].join(" "))
+            }
+            Expr::Atom(_) => format!("not {}", prose_condition(inner)),
+            _ => format!("not ({})", prose_condition(inner)),
+        },
+    }
+}
+
+fn explain_action(action: &Action) -> String {
+    let spell = title_case(spell_name(&action.spell));
+    match &action.condition {
+        Some(condition) => format!("Cast {} when {}.", spell, prose_condition(condition)),
+        None => format!("Cast {} whenever it is available.", spell),
+    }
+}
+
+fn parse_simc_condition(raw: &str) -> Expr {
+    let tokens = logical_tokens(raw);
+    parse_expr(&tokens.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+}
+
+fn has_complementary_atoms(parts: &[Expr]) -> bool {
+    parts.iter().any(|part| match unwrap_single_group(part) {
+        Expr::Not(inner) => parts
+            .iter()
+            .any(|other| unwrap_single_group(other) == unwrap_single_group(inner)),
+        _ => false,
+    })
+}
+
+fn constant_value(expr: &Expr) -> Option<bool> {
+    match unwrap_single_group(expr) {
+        Expr::Atom(_) => None,
+        Expr::Not(inner) => constant_value(inner).map(|value| !value),
+        Expr::And(parts) | Expr::Or(parts) => {
+            let absorbing = matches!(unwrap_single_group(expr), Expr::Or(_));
+            let values: Vec<Option<bool>> = parts.iter().map(constant_value).collect();
+            if has_complementary_atoms(parts) || values.contains(&Some(absorbing)) {
+                Some(absorbing)
+            } else if values.iter().all(|value| *value == Some(!absorbing)) {
+                Some(!absorbing)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+fn format_to_simc(expr: &Expr) -> String {
+    match unwrap_single_group(expr) {
+        Expr::Atom(tokens) => tokens
+            .iter()
+            .map(|token| match token.as_str() {
+                "and" => "&",
+                "or" => "|",
+                "not" => "!",
+                other => other,
+            })
+            .collect(),
+        Expr::And(parts) => parts
+            .iter()
+            .map(|part| match unwrap_single_group(part) {
+                Expr::Or(sub_parts) if sub_parts.len() > 1 => format!("({})", format_to_simc(part)),
+                _ => format_to_simc(part),
+            })
+            .collect::<Vec<_>>()
+            .join("&"),
+        Expr::Or(parts) => parts
+            .iter()
+            .map(format_to_simc)
+            .collect::<Vec<_>>()
+            .join("|"),
+        Expr::Not(inner) => match unwrap_single_group(inner) {
+            Expr::Atom(_) | Expr::Not(_) => format!("!{}", format_to_simc(inner)),
+            _ => format!("!({})", format_to_simc(inner)),
+        },
+    }
+}
+
+fn split_top_level_commas(text: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                pieces.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    pieces.push(&text[start..]);
+    pieces
+}
+
+fn split_trailing_comment(spell_raw: &str) -> (&str, Option<String>) {
+    let last = split_top_level_commas(spell_raw).pop().unwrap_or_default();
+    let last_start = spell_raw.len() - last.len();
+    let mut depth = 0usize;
+    for (i, c) in last.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '#' if depth == 0 && last[..i].ends_with(char::is_whitespace) => {
+                let comment = last[i + 1..].trim();
+                return (
+                    spell_raw[..last_start + i].trim_end(),
+                    (!comment.is_empty()).then(|| comment.to_string()),
+                );
+            }
+            _ => {}
+        }
+    }
+    (spell_raw, None)
+}
+
+fn split_modifiers(spell_raw: &str) -> (String, Vec<(String, String)>) {
+    let mut pieces = split_top_level_commas(spell_raw).into_iter();
+    let mut spell = pieces.next().unwrap_or("").trim().to_string();
+    let mut modifiers: Vec<(String, String)> = Vec::new();
+
+    for piece in pieces {
+        if let Some((key, value)) = piece.split_once('=') {
+            modifiers.push((key.trim().to_string(), value.trim().to_string()));
+        } else if let Some((_, value)) = modifiers.last_mut() {
+            value.push(',');
+            value.push_str(piece.trim());
+        } else {
+            spell.push(',');
+            spell.push_str(piece.trim());
+        }
+    }
+
+    (spell, modifiers)
+}
+
+fn variable_assignment(modifiers: &[(String, String)]) -> Option<String> {
+    let value = |name: &str| {
+        modifiers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let variable = value("name")?;
+    let amount = value("value").unwrap_or("0");
+    Some(match value("op").unwrap_or("set") {
+        "add" => format!("{} += {}", variable, amount),
+        "sub" => format!("{} -= {}", variable, amount),
+        "mul" => format!("{} *= {}", variable, amount),
+        "div" => format!("{} /= {}", variable, amount),
+        "min" => format!("{} = min({}, {})", variable, variable, amount),
+        "max" => format!("{} = max({}, {})", variable, variable, amount),
+        "reset" => format!("{} = default", variable),
+        "setif" => format!(
+            "{} = {} ? {} : {}",
+            variable,
+            value("condition").unwrap_or("0"),
+            amount,
+            value("value_else").unwrap_or("0")
+        ),
+        _ => format!("{} = {}", variable, amount),
+    })
+}
+
+fn wait_description(name: &str, modifiers: &[(String, String)]) -> String {
+    let mut result = match modifiers.iter().find(|(key, _)| key == "sec") {
+        Some((_, sec)) if sec == "1" => "wait 1 second".to_string(),
+        Some((_, sec)) if sec.parse::<f64>().is_ok() => format!("wait {} seconds", sec),
+        Some((_, sec)) => format!("wait for {}", sec),
+        None if name == "wait_until" => "wait until".to_string(),
+        None => "wait".to_string(),
+    };
+    for (key, value) in modifiers.iter().filter(|(key, _)| key != "sec") {
+        result.push_str(&format!(",{}={}", key, value));
+    }
+    result
+}
+
+fn display_spell(spell: &str) -> String {
+    let (name, modifiers) = split_modifiers(spell);
+    if name == "variable" {
+        if let Some(assignment) = variable_assignment(&modifiers) {
+            return format!("variable: {}", assignment);
+        }
+    }
+    if name == "wait" || name == "wait_until" {
+        return wait_description(&name, &modifiers);
+    }
+    let item = modifiers
+        .iter()
+        .find(|(key, _)| key == "name" || key == "slot")
+        .filter(|_| name == "use_item");
+    let mut result = match item {
+        Some((_, item)) => format!("use item: {}", item),
+        None => name,
+    };
+    for (key, value) in &modifiers {
+        let shown_as_item = item.is_some() && (key == "name" || key == "slot");
+        if !shown_as_item && key != "cycle_targets" && key != "max_cycle_targets" {
+            result.push_str(&format!(",{}={}", key, value));
+        }
+    }
+    result
+}
+
+fn targeting_hint(spell: &str) -> Option<String> {
+    let (_, modifiers) = split_modifiers(spell);
+    let value = |name: &str| {
+        modifiers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    match (value("cycle_targets"), value("max_cycle_targets")) {
+        (_, Some(max)) => Some(format!("cycles up to {} targets", max)),
+        (Some(cycle), None) if cycle != "0" => Some("cycles through targets".to_string()),
+        _ => None,
+    }
+}
+
+fn check_nesting(raw: &str) -> Result<(), String> {
+    let mut depth = 0usize;
+    let mut negations = 0usize;
+    for c in raw.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Err("unmatched `)` in condition".to_string()),
+            ')' => depth -= 1,
+            '!' => negations += 1,
+            _ if c.is_whitespace() => {}
+            _ => negations = 0,
+        }
+        if depth + negations > MAX_CONDITION_DEPTH {
+            return Err(format!(
+                "condition nests more than {} levels deep",
+                MAX_CONDITION_DEPTH
+            ));
+        }
+    }
+    if depth > 0 {
+        return Err(format!("{} unclosed `(` in condition", depth));
+    }
+    Ok(())
+}
+
+// Re-reads a stored raw condition, skipping ones `check_nesting` rejected so
+// a pathological paste cannot recurse through the parser later on.
+fn parse_checked(raw: &str) -> Option<Expr> {
+    check_nesting(raw).ok().map(|_| parse_simc_condition(raw))
+}
+
+fn process_line(line: &str, settings: &Settings) -> Option<(String, Action)> {
+    let (when_raw, spell_raw) = if let Some((a, b)) = line.split_once("+=/") {
+        (a.trim(), b.trim())
+    } else if let Some((a, b)) = line.split_once('=') {
+        (a.trim(), b.trim().trim_start_matches('/'))
+    } else {
+        return None;
+    };
+
+    let when = when_raw
+        .strip_prefix("actions.")
+        .unwrap_or(when_raw)
+        .to_string();
+
+    let (spell_raw, annotation) = split_trailing_comment(spell_raw);
+    let (mut spell, modifiers) = split_modifiers(spell_raw);
+    if when.is_empty() || spell.trim().is_empty() {
+        return None;
+    }
+
+    let mut condition_opt = None;
+    let mut interrupt_opt = None;
+    let mut chain = false;
+    for (key, value) in modifiers {
+        match key.as_str() {
+            "if" => condition_opt = Some(value),
+            "interrupt_if" => interrupt_opt = Some(value),
+            "chain" => chain = value != "0",
+            _ => spell.push_str(&format!(",{}={}", key, value)),
+        }
+    }
+
+    let parse = |raw: &String| check_nesting(raw).map(|_| transform_condition(raw, settings));
+    let condition = condition_opt.as_ref().map(parse).transpose();
+    let interrupt = interrupt_opt.as_ref().map(parse).transpose();
+    let (condition, interrupt, error) = match (condition, interrupt) {
+        (Ok(condition), Ok(interrupt)) => (condition, interrupt, None),
+        (Err(e), _) | (_, Err(e)) => (None, None, Some(e)),
+    };
+
+    let action = Action {
+        spell,
+        condition,
+        interrupt,
+        chain,
+        raw_condition: condition_opt,
+        raw_interrupt: interrupt_opt,
+        annotation,
+        comment: false,
+        error,
+    };
+
+    Some((when, action))
+}
+
+fn format_condition_text(condition: &Expr, settings: &Settings) -> String {
+    if !settings.tree_view {
+        return pretty_format_condition(
+            condition,
+            1,
+            settings.operator_style,
+            settings.indentation,
+        );
+    }
+    format_condition_tree(condition, settings.operator_style)
+        .lines()
+        .map(|line| format!("{}{}", settings.indentation.unit(), line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_action(action: &Action, settings: &Settings) -> String {
+    let mut result = display_spell(&action.spell);
+    if action.chain {
+        result.push_str(" [recast while channeling]");
+    }
+    if let Some(hint) = targeting_hint(&action.spell) {
+        result.push_str(&format!(" [{}]", hint));
+    }
+    if let Some(condition) = &action.condition {
+        result.push_str(&format!(
+            ":\n{}",
+            format_condition_text(condition, settings)
+        ));
+    }
+
+    if let Some(interrupt) = &action.interrupt {
+        result.push_str(&format!(
+            "\ninterrupt when:\n{}",
+            format_condition_text(interrupt, settings)
+        ));
+    }
+
+    if let Some(annotation) = &action.annotation {
+        result.push_str(&format!("  # {}", annotation));
+    }
+
+    if let Some(error) = &action.error {
+        result.push_str(&format!("\n    error: {}", error));
+    }
+
+    result
+}
+
+fn action_numbers(actions: &[Action]) -> Vec<usize> {
+    let mut number = 0;
+    actions
+        .iter()
+        .map(|action| {
+            if !action.comment {
+                number += 1;
+            }
+            number
+        })
+        .collect()
+}
+
+fn display_numbers(
+    groups: &BTreeMap<String, Vec<Action>>,
+    settings: &Settings,
+) -> BTreeMap<String, Vec<usize>> {
+    let mut offset = 0;
+    groups
+        .iter()
+        .map(|(when, actions)| {
+            let numbers: Vec<usize> = action_numbers(actions)
+                .into_iter()
+                .map(|number| number + offset)
+                .collect();
+            if settings.continuous_numbering {
+                offset = numbers.last().copied().unwrap_or(offset);
+            }
+            (when.clone(), numbers)
+        })
+        .collect()
+}
+
+fn format_group(actions: &[Action], numbers: &[usize], settings: &Settings) -> String {
+    let mut runs = vec![None; actions.len()];
+    let mut merged = vec![false; actions.len()];
+    if settings.merge_shared_conditions {
+        for (start, len) in shared_condition_runs(actions) {
+            runs[start] = Some(len);
+            merged[start..start + len].fill(true);
+        }
+    }
+
+    actions
+        .iter()
+        .zip(numbers)
+        .enumerate()
+        .map(|(i, (action, number))| match &action.annotation {
+            Some(comment) if action.comment => format!("# {}", comment),
+            _ if merged[i] => {
+                let own = Action {
+                    condition: None,
+                    ..action.clone()
+                };
+                let text = format!("({}) {}", number, format_action(&own, settings));
+                match (runs[i], &action.condition) {
+                    (Some(len), Some(condition)) => format!(
+                        "the next {} actions share:\n{}\n\n{}",
+                        len,
+                        format_condition_text(condition, settings),
+                        text
+                    ),
+                    _ => text,
+                }
+            }
+            _ => format!("({}) {}", number, format_action(action, settings)),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn join_continuation_lines(apl: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    let mut pending = false;
+
+    for line in apl.lines() {
+        let trimmed = line.trim();
+        if pending && (trimmed.is_empty() || trimmed.starts_with('#')) {
+            skipped.push(line.to_string());
+            continue;
+        }
+        match lines.last_mut() {
+            Some(prev) if pending => {
+                prev.push_str(trimmed);
+                let at = lines.len() - 1;
+                lines.splice(at..at, skipped.drain(..));
+            }
+            _ => lines.push(line.to_string()),
+        }
+        pending = lines.last().is_some_and(|last| {
+            let last = last.trim();
+            !last.starts_with('#') && last.ends_with(['&', '|', '('])
+        });
+    }
+    lines.append(&mut skipped);
+
+    lines.join("\n")
+}
+
+fn continues_action(prev: &str, line: &str) -> bool {
+    if !prev.starts_with("actions")
+        || line.is_empty()
+        || line.starts_with("actions")
+        || line.starts_with('#')
+        || profile_setting(line).is_some()
+    {
+        return false;
+    }
+    let (Some(end), Some(start)) = (prev.chars().next_back(), line.chars().next()) else {
+        return false;
+    };
+    if ",&|(!<>=+-*/%".contains(end) || ",&|)<>=+-*/%".contains(start) {
+        return true;
+    }
+    let token_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | '\'');
+    token_char(end) && token_char(start) && !KEY_VALUE_RE.is_match(line)
+}
+
+fn normalize_apl(apl: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    for raw in apl.lines() {
+        let line = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+        let is_continuation = lines
+            .last()
+            .is_some_and(|prev| continues_action(prev, &line));
+
+        match lines.last_mut() {
+            Some(prev) if is_continuation => prev.push_str(&line),
+            _ => lines.push(line),
+        }
+    }
+
+    lines
+        .iter()
+        .map(|line| line.trim_end_matches(',').trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn replace_in_apl(apl: &str, find: &str, replace: &str, use_regex: bool) -> Result<String, String> {
+    if find.is_empty() {
+        return Ok(apl.to_string());
+    }
+    if use_regex {
+        let re = Regex::new(find).map_err(|e| e.to_string())?;
+        Ok(re.replace_all(apl, replace).into_owned())
+    } else {
+        Ok(apl.replace(find, replace))
+    }
+}
+
+fn format_action_simc(when: &str, action: &Action) -> String {
+    if action.comment {
+        return format!("# {}", action.annotation.as_deref().unwrap_or_default());
+    }
+    let when = when.rsplit_once(" / ").map_or(when, |(_, list)| list);
+    let mut line = if when == "actions" {
+        format!("actions+=/{}", action.spell)
+    } else {
+        format!("actions.{}+=/{}", when, action.spell)
+    };
+    if action.chain {
+        line.push_str(",chain=1");
+    }
+    if let Some(raw) = &action.raw_interrupt {
+        let simc = parse_checked(raw).map_or_else(|| raw.clone(), |expr| format_to_simc(&expr));
+        line.push_str(&format!(",interrupt_if={}", simc));
+    }
+    if let Some(raw) = &action.raw_condition {
+        let simc = parse_checked(raw).map_or_else(|| raw.clone(), |expr| format_to_simc(&expr));
+        line.push_str(&format!(",if={}", simc));
+    }
+    if let Some(annotation) = &action.annotation {
+        line.push_str(&format!(" # {}", annotation));
+    }
+    line
+}
+
+fn format_group_simc(when: &str, actions: &[Action]) -> String {
+    actions
+        .iter()
+        .map(|action| format_action_simc(when, action))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_groups(groups: &BTreeMap<String, Vec<Action>>, settings: &Settings) -> String {
+    let numbers = display_numbers(groups, settings);
+    if settings.group_by_spell {
+        return group_by_spell(groups)
+            .into_iter()
+            .map(|(spell, usages)| {
+                let body = usages
+                    .iter()
+                    .map(|(when, index, action)| {
+                        format!(
+                            "{}\n({}) {}",
+                            when,
+                            numbers[when][*index],
+                            format_action(action, settings)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                format!("{}\n\n{}", spell, body)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n\n");
+    }
+    groups
+        .iter()
+        .map(|(when, actions)| {
+            format!(
+                "{}\n\n{}",
+                when,
+                format_group(actions, &numbers[when], settings)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n\n")
+}
+
+fn code_fence_lines(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let trimmed = line.trim_start_matches('\t');
+            let tabs = line.len() - trimmed.len();
+            format!("{}{}", "    ".repeat(tabs), trimmed)
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_group_code_fence(when: &str, actions: &[Action], settings: &Settings) -> String {
+    let body = actions
+        .iter()
+        .zip(action_numbers(actions))
+        .map(|(action, number)| match &action.annotation {
+            Some(comment) if action.comment => format!("# {}", comment),
+            _ => format!("({}) {}", number, format_action(action, settings)),
+        })
+        .map(|text| code_fence_lines(&text))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    format!("```\n# {}\n\n{}\n```", when, body)
+}
+
+fn format_groups_code_fence(groups: &BTreeMap<String, Vec<Action>>, settings: &Settings) -> String {
+    groups
+        .iter()
+        .map(|(when, actions)| format_group_code_fence(when, actions, settings))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn outline_lines(text: &str, depth: usize, indentation: Indentation, out: &mut Vec<String>) {
+    for line in text.lines() {
+        let mut trimmed = line;
+        let mut levels = 0;
+        while let Some(rest) = trimmed.strip_prefix(indentation.unit()) {
+            trimmed = rest;
+            levels += 1;
+        }
+        out.push(format!("{}{}", "\t".repeat(depth + levels), trimmed));
+    }
+}
+
+fn format_action_outline(action: &Action, settings: &Settings) -> Vec<String> {
+    let mut spell = display_spell(&action.spell);
+    if action.chain {
+        spell.push_str(" [recast while channeling]");
+    }
+    let mut lines = vec![format!("\t{}", spell)];
+    if let Some(condition) = &action.condition {
+        outline_lines(
+            &pretty_format_condition(condition, 0, settings.operator_style, settings.indentation),
+            2,
+            settings.indentation,
+            &mut lines,
+        );
+    }
+    if let Some(interrupt) = &action.interrupt {
+        lines.push("\t\tinterrupt when:".to_string());
+        outline_lines(
+            &pretty_format_condition(interrupt, 0, settings.operator_style, settings.indentation),
+            3,
+            settings.indentation,
+            &mut lines,
+        );
+    }
+    if let Some(error) = &action.error {
+        lines.push(format!("\t\terror: {}", error));
+    }
+    lines
+}
+
+fn format_groups_outline(groups: &BTreeMap<String, Vec<Action>>, settings: &Settings) -> String {
+    groups
+        .iter()
+        .map(|(when, actions)| {
+            let mut lines = vec![when.clone()];
+            lines.extend(
+                actions
+                    .iter()
+                    .filter(|action| !action.comment)
+                    .flat_map(|action| format_action_outline(action, settings)),
+            );
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn markdown_code_block(text: &str) -> String {
+    let body = text
+        .lines()
+        .map(|line| format!("   {}", line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("   ```\n{}\n   ```", body)
+}
+
+fn format_action_markdown(number: usize, action: &Action, settings: &Settings) -> String {
+    let mut result = format!("{}. `{}`", number, display_spell(&action.spell));
+    if action.chain {
+        result.push_str(" *(recast while channeling)*");
+    }
+    if let Some(error) = &action.error {
+        result.push_str(&format!(" — ⚠ {}", error));
+    }
+    if let Some(condition) = &action.condition {
+        result.push('\n');
+        result.push_str(&markdown_code_block(&pretty_format_condition(
+            condition,
+            0,
+            settings.operator_style,
+            settings.indentation,
+        )));
+    }
+    if let Some(interrupt) = &action.interrupt {
+        result.push_str("\n   interrupt when:\n");
+        result.push_str(&markdown_code_block(&pretty_format_condition(
+            interrupt,
+            0,
+            settings.operator_style,
+            settings.indentation,
+        )));
+    }
+    result
+}
+
+fn format_groups_markdown(groups: &BTreeMap<String, Vec<Action>>, settings: &Settings) -> String {
+    groups
+        .iter()
+        .map(|(when, actions)| {
+            let items = actions
+                .iter()
+                .zip(action_numbers(actions))
+                .filter(|(action, _)| !action.comment)
+                .map(|(action, number)| format_action_markdown(number, action, settings))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("### {}\n\n{}", when, items)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_action_fields(number: usize, action: &Action, settings: &Settings) -> Vec<String> {
+    vec![
+        number.to_string(),
+        action.spell.clone(),
+        action.raw_condition.clone().unwrap_or_default(),
+        action
+            .condition
+            .as_ref()
+            .map(|condition| {
+                pretty_format_condition(condition, 0, settings.operator_style, settings.indentation)
+            })
+            .unwrap_or_default(),
+    ]
+}
+
+fn csv_row(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn format_group_csv(actions: &[Action], settings: &Settings) -> String {
+    let mut rows = vec!["priority,spell,raw_condition,formatted_condition".to_string()];
+    for (action, number) in actions.iter().zip(action_numbers(actions)) {
+        if !action.comment {
+            rows.push(csv_row(&csv_action_fields(number, action, settings)));
+        }
+    }
+    rows.join("\n")
+}
+
+fn format_groups_csv(groups: &BTreeMap<String, Vec<Action>>, settings: &Settings) -> String {
+    let mut rows = vec!["list,priority,spell,raw_condition,formatted_condition".to_string()];
+    for (when, actions) in groups {
+        for (action, number) in actions.iter().zip(action_numbers(actions)) {
+            if action.comment {
+                continue;
+            }
+            let mut fields = vec![when.clone()];
+            fields.extend(csv_action_fields(number, action, settings));
+            rows.push(csv_row(&fields));
+        }
+    }
+    rows.join("\n")
+}
+
+fn profile_setting(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim();
+    PROFILE_SETTING_KEYS
+        .contains(&key)
+        .then(|| (key.to_string(), value.trim().trim_matches('"').to_string()))
+}
+
+fn profile_settings(apl: &str) -> Vec<(String, String)> {
+    apl.lines()
+        .filter_map(|line| profile_setting(line.trim()))
+        .collect()
+}
+
+fn split_profiles(apl: &str) -> Vec<(String, String)> {
+    let mut sections: Vec<(Option<String>, Vec<&str>)> = vec![(None, Vec::new())];
+    let mut has_actions = false;
+    for line in apl.lines() {
+        let trimmed = line.trim();
+        let name = &sections.last().unwrap().0;
+        if let Some(caps) = PROFILE_HEADER_RE.captures(trimmed) {
+            let header = caps
+                .get(1)
+                .or(caps.get(2))
+                .unwrap()
+                .as_str()
+                .trim()
+                .to_string();
+            if has_actions || name.is_some() {
+                sections.push((Some(header), Vec::new()));
+                has_actions = false;
+            } else {
+                sections.last_mut().unwrap().0 = Some(header);
+            }
+        } else if trimmed.starts_with("actions.precombat=") && has_actions {
+            sections.push((None, Vec::new()));
+            has_actions = false;
+        }
+        has_actions |= trimmed.starts_with("actions");
+        sections.last_mut().unwrap().1.push(line);
+    }
+    sections
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, lines))| {
+            let name = name.unwrap_or_else(|| format!("Profile {}", i + 1));
+            (name, lines.join("\n"))
+        })
+        .collect()
+}
+
+fn collect_groups(
+    apl: &str,
+    settings: &Settings,
+    prefix: &str,
+    groups: &mut BTreeMap<String, Vec<Action>>,
+) {
+    let mut pending_comments: Vec<&str> = Vec::new();
+    for line in apl.lines() {
+        let trimmed_line = line.trim();
+        if let Some(comment) = trimmed_line.strip_prefix('#') {
+            let comment = comment.trim_start_matches('#').trim();
+            if !comment.is_empty() && !PROFILE_HEADER_RE.is_match(trimmed_line) {
+                pending_comments.push(comment);
+            }
+            continue;
+        }
+        if trimmed_line.is_empty() || profile_setting(trimmed_line).is_some() {
+            continue;
+        }
+
+        if let Some((when, mut action)) = process_line(trimmed_line, settings) {
+            let group = groups.entry(format!("{}{}", prefix, when)).or_default();
+            let comments = std::mem::take(&mut pending_comments);
+            match settings.comment_mode {
+                CommentMode::Ignore => action.annotation = None,
+                CommentMode::Annotate => {
+                    let mut notes: Vec<&str> = comments;
+                    if let Some(inline) = &action.annotation {
+                        notes.push(inline);
+                    }
+                    action.annotation = (!notes.is_empty()).then(|| notes.join(" · "));
+                }
+                CommentMode::Inline => {
+                    group.extend(comments.into_iter().map(Action::comment));
+                }
+            }
+            group.push(action);
+        }
+    }
+}
+
+fn process_apl_grouped(apl: &str, settings: &Settings) -> BTreeMap<String, Vec<Action>> {
+    let mut groups = BTreeMap::new();
+    let apl = join_continuation_lines(apl);
+    let apl = if settings.normalize_whitespace {
+        normalize_apl(&apl)
+    } else {
+        apl
+    };
+
+    let profiles = split_profiles(&apl);
+    if profiles.len() == 1 {
+        collect_groups(&apl, settings, "", &mut groups);
+    } else {
+        for (name, section) in profiles {
+            collect_groups(&section, settings, &format!("{} / ", name), &mut groups);
+        }
+    }
+    groups
+}
+
+fn list_entry_conditions(
+    groups: &BTreeMap<String, Vec<Action>>,
+) -> HashMap<String, Vec<(String, Option<Expr>)>> {
+    let mut callers: HashMap<String, Vec<(String, Option<Expr>)>> = HashMap::new();
+    for (when, actions) in groups {
+        for action in actions {
+            let (name, modifiers) = split_modifiers(&action.spell);
+            if name != "run_action_list" && name != "call_action_list" {
+                continue;
+            }
+            if let Some((_, list)) = modifiers.iter().find(|(key, _)| key == "name") {
+                let profile = when.rsplit_once(" / ").map_or("", |(profile, _)| profile);
+                let list = if profile.is_empty() {
+                    list.clone()
+                } else {
+                    format!("{} / {}", profile, list)
+                };
+                callers
+                    .entry(list)
+                    .or_default()
+                    .push((when.clone(), action.condition.clone()));
+            }
+        }
+    }
+    callers
+        .keys()
+        .map(|list| {
+            let mut path = vec![list.clone()];
+            (list.clone(), entry_paths(&callers, &mut path))
+        })
+        .collect()
+}
+
+// Every chain of calling lists from a list nothing calls down to
+// `path.last()`, with the conditions along the chain ANDed together. Lists
+// already on `path` are skipped so mutually calling lists terminate.
+fn entry_paths(
+    callers: &HashMap<String, Vec<(String, Option<Expr>)>>,
+    path: &mut Vec<String>,
+) -> Vec<(String, Option<Expr>)> {
+    let mut entries = Vec::new();
+    let list = path.last().cloned().unwrap_or_default();
+    for (caller, condition) in callers.get(&list).into_iter().flatten() {
+        if path.contains(caller) {
+            continue;
+        }
+        path.push(caller.clone());
+        let ancestors = entry_paths(callers, path);
+        path.pop();
+        if !callers.contains_key(caller) {
+            entries.push((caller.clone(), condition.clone()));
+        }
+        for (chain, ancestor) in ancestors {
+            entries.push((
+                format!("{} → {}", chain, caller),
+                and_conditions(ancestor, condition.clone()),
+            ));
+        }
+    }
+    entries
+}
+
+fn and_conditions(left: Option<Expr>, right: Option<Expr>) -> Option<Expr> {
+    match (left, right) {
+        (Some(left), Some(right)) => {
+            let mut parts = Vec::new();
+            for part in [left, right] {
+                match part {
+                    Expr::And(inner) => parts.extend(inner),
+                    other => parts.push(other),
+                }
+            }
+            Some(Expr::And(parts))
+        }
+        (left, right) => left.or(right),
+    }
+}
+
+fn shared_condition_runs(actions: &[Action]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < actions.len() {
+        let mut end = start + 1;
+        if actions[start].condition.is_some() {
+            while end < actions.len() && actions[end].condition == actions[start].condition {
+                end += 1;
+            }
+        }
+        if end - start > 1 {
+            runs.push((start, end - start));
+        }
+        start = end;
+    }
+    runs
+}
+
+fn unreachable_after(actions: &[Action]) -> Vec<Option<String>> {
+    let mut blocker: Option<String> = None;
+    actions
+        .iter()
+        .map(|action| {
+            let blocked = blocker.clone().filter(|_| !action.comment);
+            let name = spell_name(&action.spell);
+            let passes_through = matches!(
+                name,
+                "" | "variable" | "call_action_list" | "auto_attack" | "snapshot_stats"
+            );
+            if blocker.is_none()
+                && !action.comment
+                && action.raw_condition.is_none()
+                && !passes_through
+            {
+                blocker = Some(name.to_string());
+            }
+            blocked
+        })
+        .collect()
+}
+
+fn spell_name(spell: &str) -> &str {
+    spell.split(',').next().unwrap_or(spell)
+}
+
+fn rage_cost_label(spell: &str) -> Option<String> {
+    let (_, rage) = RAGE_COSTS
+        .iter()
+        .find(|(name, _)| *name == spell_name(spell))?;
+    Some(if *rage < 0 {
+        format!("−{} rage", -rage)
+    } else {
+        format!("+{} rage", rage)
+    })
+}
+
+fn is_known_spell(spell: &str) -> bool {
+    KNOWN_WARRIOR_SPELLS.contains(&spell_name(spell))
+}
+
+fn group_by_spell(
+    groups: &BTreeMap<String, Vec<Action>>,
+) -> BTreeMap<String, Vec<(String, usize, Action)>> {
+    let mut by_spell = BTreeMap::new();
+    for (when, actions) in groups {
+        for (index, action) in actions.iter().enumerate() {
+            if action.comment {
+                continue;
+            }
+            by_spell
+                .entry(spell_name(&action.spell).to_string())
+                .or_insert_with(Vec::new)
+                .push((when.clone(), index, action.clone()));
+        }
+    }
+    by_spell
+}
+
+fn highlight_expression(value: &str, out: &mut Vec<(&'static str, String)>) {
+    let mut last = 0;
+    for m in TOKEN_RE.find_iter(value) {
+        if m.start() > last {
+            out.push(("", value[last..m.start()].to_string()));
+        }
+        let mut token = m.as_str();
+        if let Some(rest) = token.strip_prefix('!') {
+            out.push(("hl-operator", "!".to_string()));
+            token = rest;
+        }
+        let class = match token {
+            "<=" | ">=" | "<" | ">" | "=" | "&" | "|" | "(" | ")" => "hl-operator",
+            _ if token.starts_with(|c: char| c.is_ascii_digit()) => "hl-number",
+            _ => "",
+        };
+        out.push((class, token.to_string()));
+        last = m.end();
+    }
+    if last < value.len() {
+        out.push(("", value[last..].to_string()));
+    }
+}
+
+fn highlight_line(line: &str, out: &mut Vec<(&'static str, String)>) {
+    if line.trim_start().starts_with('#') {
+        out.push(("hl-comment", line.to_string()));
+        return;
+    }
+
+    let prefix_end = if let Some(pos) = line.find("+=/") {
+        pos + 3
+    } else if let Some(pos) = line.find('=') {
+        pos + 1
+    } else {
+        out.push(("", line.to_string()));
+        return;
+    };
+    out.push(("hl-prefix", line[..prefix_end].to_string()));
+
+    for (i, piece) in split_top_level_commas(&line[prefix_end..])
+        .into_iter()
+        .enumerate()
+    {
+        if i == 0 {
+            out.push(("hl-spell", piece.to_string()));
+            continue;
+        }
+        out.push(("", ",".to_string()));
+        if let Some((key, value)) = piece.split_once('=') {
+            out.push(("hl-keyword", format!("{}=", key)));
+            highlight_expression(value, out);
+        } else {
+            highlight_expression(piece, out);
+        }
+    }
+}
+
+fn highlight_apl(apl: &str, break_at_operators: bool) -> Vec<(&'static str, String)> {
+    let mut out = Vec::new();
+    for (i, line) in apl.split('\n').enumerate() {
+        if i > 0 {
+            out.push(("", "\n".to_string()));
+        }
+        highlight_line(line, &mut out);
+    }
+    out.push(("", " ".to_string()));
+    if break_at_operators {
+        for (class, text) in &mut out {
+            if matches!(
+                (*class, text.as_str()),
+                ("hl-operator", "&" | "|") | ("", ",")
+            ) {
+                text.push('\u{200b}');
+            }
+        }
+    }
+    out
+}
+
+const ARMS_ST_APL: &str = r#"actions.precombat=snapshot_stats
+actions=charge,if=time<=0.5|movement.distance>5
+actions+=/auto_attack
+actions+=/pummel,if=target.debuff.casting.react
+actions+=/avatar,if=cooldown.colossus_smash.remains<8|target.time_to_die<20
+actions+=/colossus_smash
+actions+=/run_action_list,name=execute,if=(talent.massacre.enabled&target.health.pct<35)|target.health.pct<20
+actions+=/run_action_list,name=single_target
+actions.execute=skullsplitter,if=rage<40
+actions.execute+=/mortal_strike,if=debuff.executioners_precision.stack=2&!dot.ravager.remains
+actions.execute+=/execute,if=rage>=40|buff.sudden_death.react
+actions.execute+=/overpower,if=rage<40
+actions.single_target=rend,if=dot.rend.remains<=gcd.max*3
+actions.single_target+=/thunderclap,if=dot.rend.remains<=gcd&active_enemies>=2
+actions.single_target+=/mortal_strike
+actions.single_target+=/overpower,if=buff.opportunist.up|cooldown.overpower.charges=2
+actions.single_target+=/execute,if=buff.sudden_death.react
+actions.single_target+=/bladestorm,if=debuff.colossus_smash.up
+actions.single_target+=/slam,if=rage>=30"#;
+
+const ARMS_AOE_APL: &str = r#"actions.precombat=snapshot_stats
+actions=charge,if=time<=0.5|movement.distance>5
+actions+=/auto_attack
+actions+=/sweeping_strikes,if=active_enemies>1
+actions+=/run_action_list,name=aoe,if=active_enemies>2
+actions.aoe=thunderclap,if=dot.rend.remains<=gcd.max*3
+actions.aoe+=/avatar
+actions.aoe+=/warbreaker
+actions.aoe+=/bladestorm,if=buff.sweeping_strikes.down|cooldown.sweeping_strikes.remains>10
+actions.aoe+=/cleave,if=buff.martial_prowess.stack=2
+actions.aoe+=/mortal_strike,if=debuff.executioners_precision.stack=2
+actions.aoe+=/execute,if=buff.sudden_death.react|rage>=40
+actions.aoe+=/overpower
+actions.aoe+=/whirlwind,if=talent.fervor_of_battle"#;
+
+const FURY_ST_APL: &str = r#"actions.precombat=snapshot_stats
+actions=charge,if=time<=0.5|movement.distance>5
+actions+=/auto_attack
+actions+=/pummel,if=target.debuff.casting.react
+actions+=/recklessness,if=!talent.anger_management|cooldown.avatar.remains<1|target.time_to_die<15
+actions+=/avatar,if=buff.recklessness.up|target.time_to_die<20
+actions+=/run_action_list,name=single_target
+actions.single_target=rampage,if=rage>=115|buff.enrage.remains<gcd.max
+actions.single_target+=/bloodthirst,if=!buff.enrage.up|buff.bloodcraze.stack>=3
+actions.single_target+=/execute,if=buff.ashen_juggernaut.stack<=2|buff.sudden_death.react
+actions.single_target+=/raging_blow,if=cooldown.raging_blow.charges=2|buff.enrage.up
+actions.single_target+=/crushing_blow
+actions.single_target+=/onslaught,if=talent.tenderize&buff.enrage.up
+actions.single_target+=/whirlwind"#;
+
+const FURY_AOE_APL: &str = r#"actions.precombat=snapshot_stats
+actions=charge,if=time<=0.5|movement.distance>5
+actions+=/auto_attack
+actions+=/run_action_list,name=multi_target,if=active_enemies>=2
+actions.multi_target=recklessness
+actions.multi_target+=/avatar,if=buff.recklessness.up
+actions.multi_target+=/whirlwind,if=buff.meat_cleaver.stack=0
+actions.multi_target+=/thunderous_roar,if=buff.enrage.up
+actions.multi_target+=/odyns_fury,if=active_enemies>1&buff.enrage.up
+actions.multi_target+=/rampage,if=rage>=115|buff.enrage.down
+actions.multi_target+=/bloodthirst,if=!buff.enrage.up
+actions.multi_target+=/raging_blow,if=buff.meat_cleaver.up
+actions.multi_target+=/whirlwind"#;
+
+const EXAMPLE_APLS: &[(&str, &str)] = &[
+    ("Arms — single target", ARMS_ST_APL),
+    ("Arms — AoE", ARMS_AOE_APL),
+    ("Fury — single target", FURY_ST_APL),
+    ("Fury — AoE", FURY_AOE_APL),
+];
+
+const PROFILES_STORAGE_KEY: &str = "apl-formatter.profiles";
+const PINS_STORAGE_KEY: &str = "apl-formatter.pins";
+const NOTES_STORAGE_KEY: &str = "apl-formatter.notes";
+const CUSTOM_RULES_STORAGE_KEY: &str = "apl-formatter.custom-rules";
+const ONBOARDING_STORAGE_KEY: &str = "apl-formatter.onboarding-seen";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Profile {
+    name: String,
+    input: String,
+}
+
+impl Profile {
+    fn new(name: String) -> Self {
+        Profile {
+            name,
+            input: String::new(),
+        }
+    }
+}
+
+fn js_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChangeKind {
+    Unchanged,
+    Added,
+    Changed,
+}
+
+fn diff_actions(previous: &[Action], current: &[Action]) -> (Vec<ChangeKind>, Vec<String>) {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let changes = current
+        .iter()
+        .map(|action| {
+            let occurrence = seen.entry(action.spell.as_str()).or_default();
+            let matching = previous
+                .iter()
+                .filter(|prev| prev.spell == action.spell)
+                .nth(*occurrence);
+            *occurrence += 1;
+            match matching {
+                None => ChangeKind::Added,
+                Some(prev) if prev == action => ChangeKind::Unchanged,
+                Some(_) => ChangeKind::Changed,
+            }
+        })
+        .collect();
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let removed = previous
+        .iter()
+        .filter(|prev| {
+            let occurrence = seen.entry(prev.spell.as_str()).or_default();
+            *occurrence += 1;
+            current.iter().filter(|a| a.spell == prev.spell).count() < *occurrence
+        })
+        .map(|prev| prev.spell.clone())
+        .collect();
+
+    (changes, removed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Execute,
+    Cooldowns,
+}
+
+impl Phase {
+    fn class(self) -> &'static str {
+        match self {
+            Phase::Execute => "badge-execute",
+            Phase::Cooldowns => "badge-cooldowns",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Phase::Execute => "execute phase",
+            Phase::Cooldowns => "cooldown window",
+        }
+    }
+}
+
+fn collect_atoms(expr: &Expr, atoms: &mut Vec<String>) {
+    match expr {
+        Expr::Atom(tokens) => atoms.push(tokens.join(" ")),
+        Expr::And(parts) | Expr::Or(parts) => {
+            for part in parts {
+                collect_atoms(part, atoms);
+            }
+        }
+        Expr::Not(inner) => collect_atoms(inner, atoms),
+    }
+}
+
+fn gear_dependencies(groups: &BTreeMap<String, Vec<Action>>) -> BTreeSet<String> {
+    let mut gear = BTreeSet::new();
+    for action in groups.values().flatten() {
+        for raw in [&action.raw_condition, &action.raw_interrupt]
+            .into_iter()
+            .flatten()
+        {
+            let Some(expr) = parse_checked(raw) else {
+                continue;
+            };
+            let mut atoms = Vec::new();
+            collect_atoms(&expr, &mut atoms);
+            for atom in atoms {
+                for token in atom.split(' ') {
+                    if let Some(m) = GEAR_TOKEN_RE.find(token) {
+                        gear.insert(m.as_str().to_string());
+                    }
+                }
+            }
+        }
+    }
+    gear
+}
+
+fn root_kind(expr: &Expr) -> &'static str {
+    match unwrap_single_group(expr) {
+        Expr::Atom(_) => "atom",
+        Expr::And(_) => "and",
+        Expr::Or(_) => "or",
+        Expr::Not(_) => "not",
+    }
+}
+
+fn condition_summary(expr: &Expr, max_atoms: usize, max_chars: usize) -> String {
+    let mut atoms = Vec::new();
+    collect_atoms(expr, &mut atoms);
+    let mut summary = atoms
+        .iter()
+        .take(max_atoms)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" · ");
+    if summary.chars().count() > max_chars {
+        summary = summary.chars().take(max_chars).collect::<String>();
+        summary.push('…');
+    } else if atoms.len() > max_atoms {
+        summary.push_str(" …");
+    }
+    summary
+}
+
+fn classify_phases(expr: &Expr) -> Vec<Phase> {
+    let mut atoms = Vec::new();
+    collect_atoms(expr, &mut atoms);
+
+    let mut phases = Vec::new();
+    if atoms.iter().any(|atom| EXECUTE_PHASE_RE.is_match(atom)) {
+        phases.push(Phase::Execute);
+    }
+    if atoms.iter().any(|atom| COOLDOWN_PHASE_RE.is_match(atom)) {
+        phases.push(Phase::Cooldowns);
+    }
+    phases
+}
+
+fn copy_to_clipboard(text: &str) {
+    let _ = document::eval(&format!(
+        "navigator.clipboard.writeText({});",
+        js_string(text)
+    ));
+}
+
+fn download_text(filename: &str, text: &str) {
+    let _ = document::eval(&format!(
+        "const a = document.createElement('a');
+        a.href = URL.createObjectURL(new Blob([{}], {{ type: 'text/plain' }}));
+        a.download = {};
+        a.click();
+        URL.revokeObjectURL(a.href);",
+        js_string(text),
+        js_string(filename)
+    ));
+}
+
+fn encode_uri_component(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn share_link(page_url: &str, apl: &str) -> String {
+    format!("{}#apl={}", page_url, encode_uri_component(apl))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Bundle {
+    input: String,
+    settings: Settings,
+}
+
+fn bundle_json(input: &str, settings: &Settings) -> String {
+    serde_json::to_string_pretty(&Bundle {
+        input: input.to_string(),
+        settings: settings.clone(),
+    })
+    .unwrap()
+}
+
+fn parse_bundle(json: &str) -> Result<Bundle, String> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+async fn pick_text_file() -> Option<String> {
+    document::eval(
+        "return await new Promise((resolve) => {
+            const input = document.createElement('input');
+            input.type = 'file';
+            input.accept = '.json,application/json';
+            input.onchange = () => input.files[0] ? input.files[0].text().then(resolve) : resolve(null);
+            input.oncancel = () => resolve(null);
+            input.click();
+        });",
+    )
+    .join::<Option<String>>()
+    .await
+    .ok()
+    .flatten()
+}
+
+async fn load_page_url() -> Option<String> {
+    document::eval("return location.origin + location.pathname;")
+        .join::<String>()
+        .await
+        .ok()
+}
+
+async fn load_shared_apl() -> Option<String> {
+    document::eval(
+        "if (!location.hash.startsWith('#apl=')) return null;
+        const apl = decodeURIComponent(location.hash.slice(5));
+        history.replaceState(null, '', location.pathname + location.search);
+        return apl;",
+    )
+    .join::<Option<String>>()
+    .await
+    .ok()
+    .flatten()
+}
+
+async fn load_from_storage(key: &str) -> Option<String> {
+    document::eval(&format!("return localStorage.getItem({});", js_string(key)))
+        .join::<Option<String>>()
+        .await
+        .ok()
+        .flatten()
+}
+
+fn save_to_storage(key: &str, value: &str) {
+    let _ = document::eval(&format!(
+        "localStorage.setItem({}, {});",
+        js_string(key),
+        js_string(value)
+    ));
+}
+
+fn set_document_title(title: &str) {
+    let _ = document::eval(&format!("document.title = {};", js_string(title)));
+}
+
+fn set_favicon(color: &str) {
+    let svg = format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 16 16'><rect width='16' height='16' rx='3' fill='{}'/></svg>",
+        color
+    );
+    let href = format!("data:image/svg+xml,{}", svg.replace('#', "%23"));
+    let _ = document::eval(&format!(
+        "let link = document.querySelector('link[rel=icon]');
+        if (!link) {{
+            link = document.createElement('link');
+            link.rel = 'icon';
+            document.head.appendChild(link);
+        }}
+        link.href = {};",
+        js_string(&href)
+    ));
+}
+
+fn show_toast(mut toast: Signal<Option<String>>, message: String) {
+    toast.set(Some(message.clone()));
+    spawn(async move {
+        let _ = document::eval("return new Promise((resolve) => setTimeout(resolve, 2000));")
+            .join::<serde_json::Value>()
+            .await;
+        if toast.peek().as_deref() == Some(message.as_str()) {
+            toast.set(None);
+        }
+    });
+}
+
+fn now_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::window()
+            .and_then(|w| w.performance())
+            .map(|p| p.now())
+            .unwrap_or(0.0)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+}
+
+fn action_key(profile: &str, group: &str, action: &Action) -> String {
+    format!(
+        "{}/{}/{}/{}",
+        profile,
+        group,
+        action.spell,
+        action.raw_condition.as_deref().unwrap_or_default()
+    )
+}
+
+fn toggle_pin(mut pins: Signal<Vec<String>>, key: &str) {
+    let mut pins = pins.write();
+    if let Some(pos) = pins.iter().position(|pin| pin == key) {
+        pins.remove(pos);
+    } else {
+        pins.push(key.to_string());
+    }
+}
+
+fn move_focus(
+    focused: Option<(usize, usize)>,
+    group_sizes: &[usize],
+    key: &Key,
+) -> Option<(usize, usize)> {
+    if group_sizes.is_empty() {
+        return None;
+    }
+    if !matches!(
+        key,
+        Key::ArrowUp | Key::ArrowDown | Key::ArrowLeft | Key::ArrowRight
+    ) {
+        return None;
+    }
+    let Some((group, index)) = focused else {
+        return Some((0, 0));
+    };
+    let group = group.min(group_sizes.len() - 1);
+    let (group, index) = match key {
+        Key::ArrowUp => (group, index.saturating_sub(1)),
+        Key::ArrowDown => (group, index + 1),
+        Key::ArrowLeft => (group.saturating_sub(1), index),
+        _ => ((group + 1).min(group_sizes.len() - 1), index),
+    };
+    let last = group_sizes[group].saturating_sub(1);
+    Some((group, index.min(last)))
+}
+
+fn focus_element(id: &str) {
+    let _ = document::eval(&format!(
+        "document.getElementById({})?.focus();",
+        js_string(id)
+    ));
+}
+
+fn set_note(mut notes: Signal<HashMap<String, String>>, key: &str, text: String) {
+    let mut notes = notes.write();
+    if text.trim().is_empty() {
+        notes.remove(key);
+    } else {
+        notes.insert(key.to_string(), text);
+    }
+}
+
+fn depth_class(depth: usize, depth_colors: bool) -> String {
+    if depth_colors && depth >= DEPTH_COLOR_THRESHOLD {
+        format!("cond-block depth-{}", depth.min(DEPTH_COLOR_LEVELS))
+    } else {
+        "cond-block".to_string()
+    }
+}
+
+fn fits_horizontally(parts: &[Expr]) -> bool {
+    let mut width = 0;
+    for part in parts {
+        match unwrap_single_group(part) {
+            Expr::Atom(tokens) => width += tokens.join(" ").len(),
+            Expr::Not(inner) => match unwrap_single_group(inner) {
+                Expr::Atom(tokens) => width += tokens.join(" ").len() + 4,
+                _ => return false,
+            },
+            _ => return false,
+        }
+    }
+    parts.len() <= HORIZONTAL_MAX_BRANCHES && width <= HORIZONTAL_MAX_WIDTH
+}
+
+#[component]
+fn ConditionView(
+    expr: Expr,
+    nested: bool,
+    collapse_over: Option<usize>,
+    style: OperatorStyle,
+    horizontal: bool,
+    depth: usize,
+    depth_colors: bool,
+) -> Element {
+    match unwrap_single_group(&expr).clone() {
+        Expr::Atom(tokens) => rsx! {
+            span { class: "cond-atom", {tokens.join(" ")} }
+        },
+        Expr::And(parts) => rsx! {
+            for (i, part) in parts.into_iter().enumerate() {
+                if i > 0 {
+                    span { class: "cond-op", " {style.and()} " }
+                }
+                ConditionView { expr: part, nested: true, collapse_over, style, horizontal, depth, depth_colors }
+            }
+        },
+        Expr::Or(parts) => rsx! {
+            OrNode { parts, nested, collapse_over, style, horizontal, depth, depth_colors }
+        },
+        Expr::Not(inner) => {
+            let grouped = matches!(unwrap_single_group(&inner), Expr::And(_));
+            rsx! {
+                span { class: "cond-op", "{style.not_prefix()}" }
+                if grouped {
+                    "("
+                }
+                ConditionView { expr: *inner, nested: true, collapse_over, style, horizontal, depth, depth_colors }
+                if grouped {
+                    ")"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn OrNode(
+    parts: Vec<Expr>,
+    nested: bool,
+    collapse_over: Option<usize>,
+    style: OperatorStyle,
+    horizontal: bool,
+    depth: usize,
+    depth_colors: bool,
+) -> Element {
+    let mut expanded = use_signal(|| false);
+    let count = parts.len();
+    let collapsible = collapse_over.is_some_and(|n| count > n);
+
+    if collapsible && !expanded() {
+        return rsx! {
+            span {
+                class: "or-summary",
+                onclick: move |_| expanded.set(true),
+                "any of {count} conditions ▸"
+            }
+        };
+    }
+
+    let child_depth = if nested { depth + 1 } else { depth };
+    if horizontal && fits_horizontally(&parts) {
+        return rsx! {
+            span {
+                class: "cond-inline",
+                if nested {
+                    "("
+                }
+                for (i, part) in parts.into_iter().enumerate() {
+                    if i > 0 {
+                        span { class: "cond-op", " {style.or()} " }
+                    }
+                    ConditionView { expr: part, nested: true, collapse_over, style, horizontal, depth: child_depth, depth_colors }
+                }
+                if nested {
+                    ")"
+                }
+            }
+        };
+    }
+
+    let lines = rsx! {
+        for (i, part) in parts.into_iter().enumerate() {
+            div {
+                class: "cond-line",
+                if i > 0 {
+                    span { class: "cond-op", "{style.or()} " }
+                }
+                ConditionView { expr: part, nested: true, collapse_over, style, horizontal, depth: child_depth, depth_colors }
+            }
+        }
+        if collapsible {
+            div {
+                class: "cond-line",
+                span {
+                    class: "or-summary",
+                    onclick: move |_| expanded.set(false),
+                    "collapse ▴"
+                }
+            }
+        }
+    };
+
+    if nested {
+        rsx! {
+            "("
+            div { class: depth_class(child_depth, depth_colors), {lines} }
+            ")"
+        }
+    } else {
+        lines
+    }
+}
+
+#[component]
+fn ActionView(
+    number: usize,
+    action: Action,
+    change: ChangeKind,
+    settings: Settings,
+    pinned: bool,
+    entered_from: Vec<(String, Option<Expr>)>,
+    unreachable_after: Option<String>,
+    merged: bool,
+    note: String,
+    on_pin: EventHandler<()>,
+    on_note: EventHandler<String>,
+) -> Element {
+    let mut editing_note = use_signal(|| false);
+    let mut condition_expanded = use_signal(|| false);
+    let mut explaining = use_signal(|| false);
+    let class = match change {
+        _ if action.error.is_some() => "action failed",
+        ChangeKind::Unchanged => "action",
+        ChangeKind::Added => "action added",
+        ChangeKind::Changed => "action changed",
+    };
+    let collapse_over = settings.or_collapse_over();
+    let condition_lines = action
+        .condition
+        .as_ref()
+        .map(|condition| {
+            pretty_format_condition(condition, 1, settings.operator_style, settings.indentation)
+                .lines()
+                .count()
+        })
+        .unwrap_or(0);
+    let condition_collapsible = settings
+        .condition_lines_over()
+        .is_some_and(|n| condition_lines > n);
+    let constant = action
+        .raw_condition
+        .as_deref()
+        .and_then(parse_checked)
+        .and_then(|expr| constant_value(&expr));
+    let phases = match &action.condition {
+        Some(condition) if settings.highlight_phases => classify_phases(condition),
+        _ => Vec::new(),
+    };
+
+    if action.comment {
+        return rsx! {
+            div { class: "action-comment", "# {action.annotation.clone().unwrap_or_default()}" }
+        };
+    }
+
+    rsx! {
+        div {
+            class,
+            title: match (change, settings.compare_reference.is_some()) {
+                (ChangeKind::Unchanged, _) => "",
+                (ChangeKind::Added, false) => "New since last edit",
+                (ChangeKind::Changed, false) => "Changed since last edit",
+                (ChangeKind::Added, true) => "Not in the reference APL",
+                (ChangeKind::Changed, true) => "Condition differs from the reference APL",
+            },
+            button {
+                class: if pinned { "pin-button pinned" } else { "pin-button" },
+                title: if pinned { "Unpin" } else { "Pin" },
+                onclick: move |_| on_pin.call(()),
+                if pinned { "★" } else { "☆" }
+            }
+            if let Some(kind) = action.condition.as_ref().map(root_kind) {
+                span {
+                    class: "root-indicator root-{kind}",
+                    title: "Top-level condition: {kind}",
+                }
+            }
+            span { class: "action-number", "({number}) " }
+            span { class: "action-spell", {display_spell(&action.spell)} }
+            if action.chain {
+                span { class: "badge", "recast while channeling" }
+            }
+            if let Some(hint) = targeting_hint(&action.spell) {
+                span { class: "badge", "{hint}" }
+            }
+            if let Some(cost) = rage_cost_label(&action.spell).filter(|_| settings.show_rage_costs) {
+                span { class: "badge badge-rage", "{cost}" }
+            }
+            match constant {
+                Some(true) => rsx! {
+                    span {
+                        class: "badge badge-error",
+                        title: "The condition holds no matter what the game state is",
+                        "always true"
+                    }
+                },
+                Some(false) => rsx! {
+                    span {
+                        class: "badge badge-error",
+                        title: "The condition fails no matter what the game state is",
+                        "always false"
+                    }
+                },
+                None => rsx! {},
+            }
+            if settings.validate_spells && action.error.is_none() && !is_known_spell(&action.spell) {
+                span {
+                    class: "badge badge-error",
+                    title: "Not a known warrior ability — check for typos",
+                    "unknown spell"
+                }
+            }
+            if let Some(blocker) = &unreachable_after {
+                span {
+                    class: "badge badge-error",
+                    title: "Follows {blocker}, which has no condition, so this is only reached while {blocker} cannot be cast",
+                    "after unconditional {blocker}"
+                }
+            }
+            for phase in phases {
+                span { class: "badge {phase.class()}", "{phase.label()}" }
+            }
+            if let Some(annotation) = &action.annotation {
+                span { class: "action-annotation", "# {annotation}" }
+            }
+            if let Some(condition) = &action.condition {
+                button {
+                    class: "export-button",
+                    title: "Copy the condition tree as Graphviz DOT",
+                    onclick: {
+                        let dot = expr_to_dot(condition);
+                        move |_| copy_to_clipboard(&dot)
+                    },
+                    "export tree (DOT)"
+                }
+            }
+            if settings.humanize && action.error.is_none() {
+                button {
+                    class: "explain-button",
+                    onclick: move |_| explaining.toggle(),
+                    "explain"
+                }
+            }
+            if explaining() && settings.humanize {
+                div {
+                    class: "explain-popover",
+                    onclick: move |_| explaining.set(false),
+                    {explain_action(&action)}
+                }
+            }
+            for (list, entry) in entered_from.iter().cloned() {
+                if let Some(entry) = entry {
+                    div { class: "cond-label", "list entered from {list} when:" }
+                    div {
+                        class: "cond-block",
+                        ConditionView { expr: entry, nested: false, collapse_over, style: settings.operator_style, horizontal: settings.horizontal_layout, depth: 0, depth_colors: settings.depth_colors }
+                    }
+                } else {
+                    div { class: "cond-label", "list entered from {list} unconditionally" }
+                }
+            }
+            if merged {
+                div { class: "cond-label", "shares the condition above" }
+            } else if let Some(condition) = action.condition {
+                if entered_from.is_empty() {
+                    ":"
+                } else {
+                    div { class: "cond-label", "and its own condition:" }
+                }
+                if condition_collapsible && !condition_expanded() {
+                    div {
+                        class: "cond-block",
+                        span { class: "ladder-summary", {condition_summary(&condition, 3, 60)} }
+                        span {
+                            class: "or-summary",
+                            onclick: move |_| condition_expanded.set(true),
+                            " show condition ({condition_lines} lines) ▸"
+                        }
+                    }
+                } else if settings.tree_view {
+                    div { class: "cond-block cond-tree", {format_condition_tree(&condition, settings.operator_style)} }
+                } else {
+                    div {
+                        class: "cond-block",
+                        ConditionView { expr: condition, nested: false, collapse_over, style: settings.operator_style, horizontal: settings.horizontal_layout, depth: 0, depth_colors: settings.depth_colors }
+                    }
+                }
+                if condition_collapsible && condition_expanded() {
+                    div {
+                        class: "cond-block",
+                        span {
+                            class: "or-summary",
+                            onclick: move |_| condition_expanded.set(false),
+                            "hide condition ▴"
+                        }
+                    }
+                }
+            }
+            if let Some(error) = action.error {
+                div { class: "action-error", "⚠ {error}" }
+            }
+            if let Some(interrupt) = action.interrupt {
+                div { class: "cond-label", "interrupt when:" }
+                if settings.tree_view {
+                    div { class: "cond-block cond-tree", {format_condition_tree(&interrupt, settings.operator_style)} }
+                } else {
+                    div {
+                        class: "cond-block",
+                        ConditionView { expr: interrupt, nested: false, collapse_over, style: settings.operator_style, horizontal: settings.horizontal_layout, depth: 0, depth_colors: settings.depth_colors }
+                    }
+                }
+            }
+            if editing_note() {
+                textarea {
+                    class: "action-note-input",
+                    rows: "2",
+                    placeholder: "Add a note…",
+                    value: "{note}",
+                    onchange: move |e| on_note.call(e.value()),
+                    onblur: move |_| editing_note.set(false),
+                    onkeydown: move |e| e.stop_propagation(),
+                }
+            } else if !note.is_empty() {
+                div {
+                    class: "action-note",
+                    title: "Click to edit",
+                    onclick: move |_| editing_note.set(true),
+                    "✎ {note}"
+                }
+            } else {
+                button {
+                    class: "note-button",
+                    onclick: move |_| editing_note.set(true),
+                    "+ note"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn App() -> Element {
+    let mut profiles = use_signal(|| vec![Profile::new("Profile 1".to_string())]);
+    let mut active = use_signal(|| 0usize);
+    let mut renaming = use_signal(|| None::<usize>);
+    let mut loaded = use_signal(|| false);
+    let mut previous_groups = use_signal(|| None::<BTreeMap<String, Vec<Action>>>);
+    let mut pins = use_signal(Vec::<String>::new);
+    let mut notes = use_signal(HashMap::<String, String>::new);
+    let mut focused = use_signal(|| None::<(usize, usize)>);
+    let mut menu_open = use_signal(|| false);
+    let mut settings = use_signal(Settings::default);
+    let mut show_onboarding = use_signal(|| false);
+    let mut find_text = use_signal(String::new);
+    let mut replace_text = use_signal(String::new);
+    let mut find_regex = use_signal(|| false);
+    let mut editing_input = use_signal(|| false);
+    let mut editor_collapsed = use_signal(|| false);
+    let toast = use_signal(|| None::<String>);
+    let mut show_glossary = use_signal(|| false);
+    let mut glossary_filter = use_signal(String::new);
+    let mut show_custom_rules = use_signal(|| false);
+    let mut show_qr = use_signal(|| false);
+    let mut page_url = use_signal(String::new);
+
+    use_future(move || async move {
+        if let Some(saved) = load_from_storage(PROFILES_STORAGE_KEY).await {
+            if let Ok(saved) = serde_json::from_str::<Vec<Profile>>(&saved) {
+                if !saved.is_empty() {
+                    profiles.set(saved);
+                }
+            }
+        }
+        if let Some(saved) = load_from_storage(PINS_STORAGE_KEY).await {
+            if let Ok(saved) = serde_json::from_str::<Vec<String>>(&saved) {
+                pins.set(saved);
+            }
+        }
+        if let Some(saved) = load_from_storage(NOTES_STORAGE_KEY).await {
+            if let Ok(saved) = serde_json::from_str::<HashMap<String, String>>(&saved) {
+                notes.set(saved);
+            }
+        }
+        if let Some(saved) = load_from_storage(CUSTOM_RULES_STORAGE_KEY).await {
+            settings.write().custom_rules = saved.into();
+        }
+        if let Some(url) = load_page_url().await {
+            page_url.set(url);
+        }
+        if let Some(shared) = load_shared_apl().await {
+            let mut profile = Profile::new("Shared".to_string());
+            profile.input = shared;
+            profiles.write().push(profile);
+            active.set(profiles.read().len() - 1);
+        }
+        if load_from_storage(ONBOARDING_STORAGE_KEY).await.is_none() {
+            show_onboarding.set(true);
+        }
+        loaded.set(true);
+    });
+
+    use_effect(move || {
+        if !loaded() {
+            return;
+        }
+        if let Ok(json) = serde_json::to_string(&*profiles.read()) {
+            save_to_storage(PROFILES_STORAGE_KEY, &json);
+        }
+    });
+
+    use_effect(move || {
+        if !loaded() {
+            return;
+        }
+        if let Ok(json) = serde_json::to_string(&*pins.read()) {
+            save_to_storage(PINS_STORAGE_KEY, &json);
+        }
+    });
+
+    use_effect(move || {
+        if !loaded() {
+            return;
+        }
+        if let Ok(json) = serde_json::to_string(&*notes.read()) {
+            save_to_storage(NOTES_STORAGE_KEY, &json);
+        }
+    });
+
+    use_effect(move || {
+        if !loaded() {
+            return;
+        }
+        save_to_storage(
+            CUSTOM_RULES_STORAGE_KEY,
+            &settings.read().custom_rules.source,
+        );
+    });
+
+    let parsed = use_memo(move || {
+        let input = profiles
+            .read()
+            .get(active())
+            .map(|p| p.input.clone())
+            .unwrap_or_default();
+        let parse_start = now_ms();
+        let groups = process_apl_grouped(&input, &settings());
+        (groups, now_ms() - parse_start)
+    });
+
+    let reference_groups = use_memo(move || {
+        settings().compare_reference.and_then(|name| {
+            EXAMPLE_APLS
+                .iter()
+                .find(|(reference, _)| *reference == name)
+                .map(|(_, apl)| process_apl_grouped(apl, &settings()))
+        })
+    });
+
+    // The groups kept from before the last paste were formatted with the old
+    // settings, so they would differ everywhere once the settings change.
+    use_effect(move || {
+        settings.read();
+        previous_groups.set(None);
+    });
+
+    use_effect(move || {
+        let title = match parsed.read().0.len() {
+            0 => "APL Formatter".to_string(),
+            1 => "APL Formatter — 1 list".to_string(),
+            lists => format!("APL Formatter — {} lists", lists),
+        };
+        set_document_title(&title);
+        set_favicon(settings().theme.accent());
+    });
+
+    let input = profiles
+        .read()
+        .get(active())
+        .map(|p| p.input.clone())
+        .unwrap_or_default();
+    let (groups, parse_ms) = parsed();
+    let diff_base = reference_groups().or(previous_groups()).unwrap_or_default();
+    let profile_name = profiles
+        .read()
+        .get(active())
+        .map(|p| p.name.clone())
+        .unwrap_or_default();
+    let pinned_actions: Vec<(String, usize, Action)> = pins()
+        .into_iter()
+        .filter_map(|key| {
+            groups.iter().find_map(|(group, actions)| {
+                let index = actions
+                    .iter()
+                    .position(|action| action_key(&profile_name, group, action) == key)?;
+                Some((group.clone(), index, actions[index].clone()))
+            })
+        })
+        .collect();
+    let action_count: usize = groups
+        .values()
+        .map(|spells| spells.iter().filter(|action| !action.comment).count())
+        .sum();
+    let numbers_by_group = display_numbers(&groups, &settings());
+    let is_empty = groups.is_empty();
+    let list_count = groups.len();
+    let group_sizes: Vec<usize> = groups.values().map(Vec::len).collect();
+    let input_is_blank = input.trim().is_empty();
+    let spell_groups = settings().group_by_spell.then(|| group_by_spell(&groups));
+    let gear = gear_dependencies(&groups);
+    let profile_values = profile_settings(&input);
+    let entry_conditions = if settings().effective_conditions {
+        list_entry_conditions(&groups)
+    } else {
+        HashMap::new()
+    };
+    let all_text = format_groups(&groups, &settings());
+    let download_all_text = all_text.clone();
+    let all_markdown = format_groups_markdown(&groups, &settings());
+    let all_csv = format_groups_csv(&groups, &settings());
+    let all_code_fence = format_groups_code_fence(&groups, &settings());
+    let all_outline = format_groups_outline(&groups, &settings());
+    let char_count = input.chars().count();
+    let find_error = replace_in_apl(&input, &find_text(), &replace_text(), find_regex()).err();
+    let link = share_link(&page_url(), &input);
+
+    rsx! {
+        head {
+            style {
+                {r#"
+                :root {
+                    --bg: #05080d;
+                    --surface: #1f2937;
+                    --surface-sunken: #111827;
+                    --surface-raised: #374151;
+                    --border: #4b5563;
+                    --text: #f3f4f6;
+                    --muted: #9ca3af;
+                    --subtle: #6b7280;
+                    --accent: #3b82f6;
+                    --spell: #93c5fd;
+                    --warn: #fbbf24;
+                }
+
+                :root:has(.theme-contrast) {
+                    --bg: #000000;
+                    --surface: #000000;
+                    --surface-sunken: #000000;
+                    --surface-raised: #1a1a1a;
+                    --border: #ffffff;
+                    --text: #ffffff;
+                    --muted: #e5e5e5;
+                    --subtle: #d4d4d4;
+                    --accent: #ffd400;
+                    --spell: #7dd3fc;
+                    --warn: #ffd400;
+                }
+
+                :root:has(.theme-light) {
+                    --bg: #f9fafb;
+                    --surface: #ffffff;
+                    --surface-sunken: #f3f4f6;
+                    --surface-raised: #e5e7eb;
+                    --border: #d1d5db;
+                    --text: #111827;
+                    --muted: #4b5563;
+                    --subtle: #6b7280;
+                    --accent: #2563eb;
+                    --spell: #1d4ed8;
+                    --warn: #b45309;
+                }
+
+                .theme-light .hl-prefix { color: #7e22ce; }
+                .theme-light .hl-operator { color: #be185d; }
+                .theme-light .hl-number { color: #047857; }
+
+                html, body {
+                    margin: 0;
+                    padding: 0;
+                    background-color: var(--bg);
+                    color: var(--text);
+                    height: 100%;
+                    font-family: "Inter", -apple-system, BlinkMacSystemFont, "Segoe UI", "Roboto", sans-serif;
+                }
+
+                * {
+                    box-sizing: border-box;
+                }
+
+                .app-container {
+                    display: flex;
+                    flex-direction: column;
+                    gap: 1.5rem;
+                    max-width: 1200px;
+                    padding: 1.5rem;
+                    margin: 0 auto;
+                    background-color: var(--bg);
+                    color: var(--text);
+                    min-height: 100vh;
+                }
+
+                .onboarding-overlay {
+                    position: fixed;
+                    inset: 0;
+                    z-index: 20;
+                    display: flex;
+                    align-items: center;
+                    justify-content: center;
+                    background-color: rgba(0, 0, 0, 0.6);
+                }
+
+                .onboarding-card {
+                    max-width: 28rem;
+                    padding: 1.5rem;
+                    border: 1px solid var(--border);
+                    border-radius: 0.5rem;
+                    background-color: var(--surface);
+                    line-height: 1.5;
+                }
+
+                .onboarding-card h2 {
+                    margin-top: 0;
+                    font-size: 1.125rem;
+                }
+
+                .qr-card {
+                    text-align: center;
+                }
+
+                .qr-code {
+                    display: block;
+                    width: 16rem;
+                    height: 16rem;
+                    margin: 0 auto 1rem;
+                    shape-rendering: crispEdges;
+                }
+
+                @media print {
+                    .qr-card button {
+                        display: none;
+                    }
+                }
+
+                .onboarding-card button {
+                    background-color: var(--accent);
+                    color: #fff;
+                    border: none;
+                    border-radius: 0.375rem;
+                    padding: 0.375rem 1rem;
+                    cursor: pointer;
+                }
+
+                .tab-bar {
+                    display: flex;
+                    flex-wrap: wrap;
+                    gap: 0.5rem;
+                    align-items: center;
+                }
+
+                .tab {
+                    display: flex;
+                    align-items: center;
+                    gap: 0.5rem;
+                    padding: 0.375rem 0.75rem;
+                    background-color: var(--surface);
+                    color: var(--muted);
+                    border: 1px solid var(--border);
+                    border-radius: 0.375rem;
+                    cursor: pointer;
+                    font-size: 0.875rem;
+                    user-select: none;
+                }
+
+                .tab.active {
+                    color: var(--text);
+                    border-color: var(--accent);
+                }
+
+                .tab-rename {
+                    width: 8rem;
+                    background-color: var(--bg);
+                    color: var(--text);
+                    border: 1px solid var(--accent);
+                    border-radius: 0.25rem;
+                    padding: 0 0.25rem;
+                    font-size: 0.875rem;
+                    outline: none;
+                }
+
+                .tab-close, .tab-add {
+                    background: none;
+                    border: none;
+                    color: inherit;
+                    cursor: pointer;
+                    font-size: 1rem;
+                    line-height: 1;
+                    padding: 0;
+                }
+
+                .tab-add {
+                    padding: 0.375rem 0.75rem;
+                    color: var(--muted);
+                    border: 1px dashed var(--border);
+                    border-radius: 0.375rem;
+                }
+
+                .tab-add:hover, .tab-close:hover {
+                    color: var(--text);
+                }
+
+                .toolbar {
+                    position: relative;
+                    display: flex;
+                    justify-content: flex-end;
+                    gap: 0.5rem;
+                }
+
+                .toolbar button.primary {
+                    background-color: var(--accent);
+                    border-color: var(--accent);
+                    color: #ffffff;
+                    font-weight: 600;
+                }
+
+                .toolbar button.primary:disabled {
+                    opacity: 0.5;
+                    cursor: default;
+                }
+
+                .toast {
+                    position: fixed;
+                    bottom: 1.5rem;
+                    left: 50%;
+                    transform: translateX(-50%);
+                    padding: 0.5rem 1rem;
+                    border-radius: 0.375rem;
+                    background-color: var(--surface-raised);
+                    color: var(--text);
+                    border: 1px solid var(--accent);
+                    font-size: 0.875rem;
+                    z-index: 20;
+                }
+
+                .toolbar-menu-button {
+                    display: none;
+                }
+
+                .toolbar-actions {
+                    display: flex;
+                    flex-wrap: wrap;
+                    gap: 0.5rem;
+                }
+
+                .toolbar button {
+                    background-color: var(--surface);
+                    color: var(--text);
+                    border: 1px solid var(--border);
+                    border-radius: 0.375rem;
+                    padding: 0.375rem 0.75rem;
+                    font-size: 0.875rem;
+                    cursor: pointer;
+                }
+
+                .toolbar button:hover, .toolbar button.active {
+                    border-color: var(--accent);
+                }
+
+                .editor {
+                    position: relative;
+                    background-color: var(--surface);
+                    border-radius: 0.375rem;
+                }
+
+                .input-backdrop {
+                    position: absolute;
+                    inset: 0;
+                    margin: 0;
+                    padding: 0.75rem;
+                    border: 1px solid transparent;
+                    font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
+                    font-size: 14px;
+                    line-height: 1.5;
+                    color: var(--text);
+                    white-space: pre-wrap;
+                    word-wrap: break-word;
+                    overflow: hidden;
+                    scrollbar-gutter: stable;
+                    pointer-events: none;
+                }
+
+                .hl-comment { color: var(--subtle); font-style: italic; }
+                .hl-prefix { color: #c084fc; }
+                .hl-spell { color: var(--spell); }
+                .hl-keyword { color: var(--warn); }
+                .hl-operator { color: #f472b6; }
+                .hl-number { color: #6ee7b7; }
 
                 .main-input {
+                    position: relative;
+                    display: block;
                     width: 100%;
                     font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
-                    background-color: #1f2937;
-                    color: #f3f4f6;
-                    border: 1px solid #4b5563;
+                    background-color: transparent;
+                    color: transparent;
+                    caret-color: var(--text);
+                    border: 1px solid var(--border);
                     padding: 0.75rem;
                     border-radius: 0.375rem;
                     outline: none;
@@ -262,76 +4054,513 @@ fn App() -> Element {
                     min-height: 200px;
                     font-size: 14px;
                     line-height: 1.5;
+                    scrollbar-gutter: stable;
+                }
+
+                .main-input:focus {
+                    border-color: var(--accent);
+                    box-shadow: 0 0 0 3px rgba(59, 130, 246, 0.1);
+                }
+
+                .main-input::placeholder {
+                    color: var(--muted);
+                }
+
+                .editor.collapsed .main-input {
+                    min-height: 0;
+                    height: 2.75rem;
+                    resize: none;
+                    overflow: hidden;
+                }
+
+                .editor-expand {
+                    position: absolute;
+                    top: 0.5rem;
+                    right: 0.5rem;
+                    padding: 0.125rem 0.5rem;
+                    font-size: 0.75rem;
+                }
+
+                .settings-bar {
+                    display: flex;
+                    flex-wrap: wrap;
+                    gap: 1rem;
+                    align-items: center;
+                    color: var(--muted);
+                    font-size: 0.875rem;
+                }
+
+                .settings-bar label {
+                    display: flex;
+                    align-items: center;
+                    gap: 0.375rem;
+                    cursor: pointer;
+                }
+
+                .status-line {
+                    margin-top: -1rem;
+                    color: var(--subtle);
+                    font-size: 0.75rem;
+                    font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
+                }
+
+                .empty-state {
+                    padding: 2rem;
+                    border: 1px dashed var(--border);
+                    border-radius: 0.5rem;
+                    color: var(--muted);
+                    text-align: center;
+                }
+
+                .empty-state-examples {
+                    display: flex;
+                    flex-wrap: wrap;
+                    justify-content: center;
+                    align-items: center;
+                    gap: 0.5rem;
+                }
+
+                .empty-state-examples button {
+                    background-color: var(--surface);
+                    color: var(--text);
+                    border: 1px solid var(--border);
+                    border-radius: 0.375rem;
+                    padding: 0.25rem 0.75rem;
+                    cursor: pointer;
+                }
+
+                .empty-state-examples button:hover {
+                    border-color: var(--accent);
+                }
+
+                .ladder {
+                    margin: 0;
+                    padding: 0.75rem 0.75rem 0.75rem 2.5rem;
+                    font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
+                    font-size: 13px;
+                }
+
+                .ladder-rung {
+                    padding: 0.125rem 0;
+                    border-left: 2px solid var(--border);
+                    padding-left: 0.5rem;
+                    white-space: nowrap;
+                    overflow: hidden;
+                    text-overflow: ellipsis;
+                }
+
+                .ladder-summary {
+                    margin-left: 1ch;
+                    color: var(--muted);
+                }
+
+                .groups-grid {
+                    display: grid;
+                    gap: 1.5rem;
+                    width: 100%;
+                    grid-template-columns: repeat(auto-fit, minmax(500px, 1fr));
+                    grid-auto-rows: 1fr;
+                }
+
+                .group-card {
+                    display: flex;
+                    flex-direction: column;
+                    border-radius: 0.5rem;
+                    box-shadow: 0 4px 8px rgba(0, 0, 0, 0.3);
+                    overflow: hidden;
+                    background-color: var(--surface);
+                    border: 1px solid var(--border);
+                    min-height: 400px;
+                }
+
+                .group-header {
+                    margin: 0;
+                    padding: 0.75rem 1rem;
+                    background-color: var(--surface-raised);
+                    color: var(--text);
+                    font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
+                    font-size: 0.875rem;
+                    font-weight: 600;
+                    text-align: center;
+                    border-bottom: 1px solid var(--border);
+                    flex-shrink: 0;
+                }
+
+                .group-content {
+                    width: 100%;
+                    flex: 1;
+                    font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
+                    background-color: var(--surface);
+                    color: var(--text);
+                    border: none;
+                    padding: 0.75rem;
+                    outline: none;
+                    resize: none;
+                    overflow-y: auto;
+                    max-height: 40rem;
+                    line-height: 1.6;
+                    white-space: pre-wrap;
+                    word-wrap: break-word;
+                    overflow-wrap: anywhere;
+                    font-size: 13px;
+                    tab-size: 4;
+                }
+
+                .action {
+                    margin-bottom: 1.6em;
+                }
+
+                .action.added, .action.changed, .action.removed {
+                    margin-left: -0.5rem;
+                    padding-left: calc(0.5rem - 2px);
+                    border-left: 2px solid transparent;
+                }
+
+                .action.added {
+                    border-left-color: #34d399;
+                }
+
+                .action.changed {
+                    border-left-color: var(--warn);
+                }
+
+                .action.removed {
+                    border-left-color: #f87171;
+                    color: var(--subtle);
+                    text-decoration: line-through;
+                }
+
+                .pinned-panel {
+                    border-radius: 0.5rem;
+                    overflow: hidden;
+                    background-color: var(--surface);
+                    border: 1px solid var(--warn);
+                }
+
+                .gear-panel {
+                    border-radius: 0.5rem;
+                    overflow: hidden;
+                    background-color: var(--surface);
+                    border: 1px solid var(--border);
+                }
+
+                .custom-rules-input {
+                    display: block;
+                    width: 100%;
+                    box-sizing: border-box;
+                    font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
+                }
+
+                .profile-settings {
+                    display: grid;
+                    grid-template-columns: max-content 1fr;
+                    gap: 0.25rem 1rem;
+                }
+
+                .profile-settings code {
+                    word-break: break-all;
+                }
+
+                .gear-item {
+                    padding: 0.125rem 0;
+                }
+
+                .pinned-group {
+                    color: var(--muted);
+                    font-size: 11px;
+                    text-transform: uppercase;
+                }
+
+                .pin-button {
+                    background: none;
+                    border: none;
+                    padding: 0;
+                    margin-right: 0.375rem;
+                    color: var(--subtle);
+                    cursor: pointer;
+                    font-size: 13px;
+                }
+
+                .pin-button:hover, .pin-button.pinned {
+                    color: var(--warn);
+                }
+
+                .action.failed {
+                    padding: 0.375rem 0.5rem;
+                    border: 1px solid #f87171;
+                    border-radius: 0.25rem;
+                }
+
+                .action-error {
+                    color: #f87171;
+                }
+
+                .action-spell {
+                    color: var(--spell);
+                }
+
+                .action-note {
+                    margin-top: 0.25rem;
+                    padding-left: var(--indent);
+                    color: var(--warn);
+                    cursor: text;
+                    white-space: pre-wrap;
+                }
+
+                .action-note-input {
+                    display: block;
+                    width: 100%;
+                    margin-top: 0.25rem;
+                    background-color: var(--surface-sunken);
+                    color: var(--text);
+                    border: 1px solid var(--border);
+                    border-radius: 0.25rem;
+                    font: inherit;
+                }
+
+                .note-button {
+                    display: none;
+                    background: none;
+                    border: none;
+                    padding: 0;
+                    margin-left: var(--indent);
+                    color: var(--subtle);
+                    font-size: 11px;
+                    cursor: pointer;
+                }
+
+                .action:hover .note-button {
+                    display: block;
+                }
+
+                .explain-button,
+                .export-button {
+                    background: none;
+                    border: none;
+                    padding: 0;
+                    margin-left: 1ch;
+                    color: var(--subtle);
+                    font-size: 11px;
+                    cursor: pointer;
+                }
+
+                .explain-button:hover,
+                .export-button:hover {
+                    color: var(--accent);
+                }
+
+                .explain-popover {
+                    position: relative;
+                    margin: 0.25rem 0 0.25rem var(--indent);
+                    padding: 0.5rem 0.75rem;
+                    border: 1px solid var(--accent);
+                    border-radius: 0.375rem;
+                    background-color: var(--surface-raised);
+                    color: var(--text);
+                    font-family: system-ui, sans-serif;
+                    white-space: normal;
+                    cursor: pointer;
+                }
+
+                .action-annotation {
+                    margin-left: 1ch;
+                    color: var(--subtle);
+                    font-style: italic;
+                }
+
+                .root-indicator {
+                    display: inline-block;
+                    width: 3px;
+                    height: 1em;
+                    margin-right: 0.375rem;
+                    vertical-align: middle;
+                    border-radius: 1px;
+                }
+
+                .root-atom { background-color: var(--subtle); }
+                .root-and { background-color: #60a5fa; }
+                .root-or { background-color: #f472b6; }
+                .root-not { background-color: #fb923c; }
+
+                .action-comment {
+                    padding: 0.25rem 0;
+                    color: var(--subtle);
+                    font-style: italic;
+                }
+
+                .cond-block {
+                    padding-left: var(--indent);
+                }
+
+                .cond-block.depth-2 { border-left: 2px solid #60a5fa; }
+                .cond-block.depth-3 { border-left: 2px solid #a78bfa; }
+                .cond-block.depth-4 { border-left: 2px solid #f472b6; }
+                .cond-block.depth-5 { border-left: 2px solid #fb923c; }
+
+                .cond-tree {
+                    white-space: pre;
+                }
+
+                .action-focus:focus {
+                    outline: 2px solid var(--accent);
+                    outline-offset: 2px;
+                    border-radius: 0.25rem;
+                }
+
+                .shared-condition {
+                    border-left: 2px solid var(--accent);
+                    margin-left: 0.25rem;
+                    padding-left: 0.5rem;
+                }
+
+                .cond-op {
+                    color: var(--muted);
+                }
+
+                .or-summary {
+                    color: #60a5fa;
+                    cursor: pointer;
+                    user-select: none;
+                }
+
+                .or-summary:hover {
+                    text-decoration: underline;
+                }
+
+                .badge {
+                    display: inline-block;
+                    margin-left: 0.5rem;
+                    padding: 0 0.375rem;
+                    border-radius: 0.25rem;
+                    background-color: var(--surface-raised);
+                    color: var(--warn);
+                    font-size: 11px;
+                    line-height: 1.5;
+                    vertical-align: middle;
+                    white-space: nowrap;
+                }
+
+                .badge-execute {
+                    color: #f87171;
+                }
+
+                .badge-rage {
+                    color: #fca5a5;
                 }
 
-                .main-input:focus {
-                    border-color: #3b82f6;
-                    box-shadow: 0 0 0 3px rgba(59, 130, 246, 0.1);
+                .badge-error {
+                    color: #f87171;
                 }
 
-                .main-input::placeholder {
-                    color: #9ca3af;
+                .badge-cooldowns {
+                    color: #c084fc;
                 }
 
-                .groups-grid {
-                    display: grid;
-                    gap: 1.5rem;
-                    width: 100%;
-                    grid-template-columns: repeat(auto-fit, minmax(500px, 1fr));
-                    grid-auto-rows: 1fr;
+                .cond-label {
+                    color: var(--muted);
                 }
 
-                .group-card {
-                    display: flex;
-                    flex-direction: column;
-                    border-radius: 0.5rem;
-                    box-shadow: 0 4px 8px rgba(0, 0, 0, 0.3);
-                    overflow: hidden;
-                    background-color: #1f2937;
-                    border: 1px solid #4b5563;
-                    min-height: 400px;
+                .copy-button {
+                    float: right;
+                    background: none;
+                    border: 1px solid var(--border);
+                    border-radius: 0.25rem;
+                    color: var(--muted);
+                    cursor: pointer;
+                    font-size: 0.75rem;
+                    padding: 0 0.5rem;
                 }
 
-                .group-header {
-                    margin: 0;
-                    padding: 0.75rem 1rem;
-                    background-color: #374151;
-                    color: #f3f4f6;
-                    font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
+                .copy-button:hover {
+                    color: var(--text);
+                    border-color: var(--accent);
+                }
+
+                .select-input {
+                    background-color: var(--surface);
+                    color: var(--text);
+                    border: 1px solid var(--border);
+                    border-radius: 0.25rem;
+                    padding: 0.125rem 0.375rem;
+                }
+
+                .find-bar {
+                    display: flex;
+                    flex-wrap: wrap;
+                    gap: 0.5rem;
+                    align-items: center;
+                    color: var(--muted);
                     font-size: 0.875rem;
-                    font-weight: 600;
-                    text-align: center;
-                    border-bottom: 1px solid #4b5563;
-                    flex-shrink: 0;
                 }
 
-                .group-content {
-                    width: 100%;
-                    flex: 1;
-                    font-family: "SF Mono", "Monaco", "Cascadia Code", "Roboto Mono", Consolas, "Courier New", monospace;
-                    background-color: #1f2937;
-                    color: #f3f4f6;
-                    border: none;
-                    padding: 0.75rem;
-                    outline: none;
-                    resize: none;
-                    overflow-y: auto;
-                    line-height: 1.6;
-                    white-space: pre-wrap;
-                    word-wrap: break-word;
-                    font-size: 13px;
-                    tab-size: 4;
+                .find-bar label {
+                    display: flex;
+                    align-items: center;
+                    gap: 0.375rem;
+                    cursor: pointer;
+                }
+
+                .find-bar button {
+                    background-color: var(--surface);
+                    color: var(--text);
+                    border: 1px solid var(--border);
+                    border-radius: 0.375rem;
+                    padding: 0.25rem 0.75rem;
+                    cursor: pointer;
+                }
+
+                .find-bar button:disabled {
+                    opacity: 0.5;
+                    cursor: default;
+                }
+
+                .text-input {
+                    background-color: var(--surface);
+                    color: var(--text);
+                    border: 1px solid var(--border);
+                    border-radius: 0.25rem;
+                    padding: 0.25rem 0.5rem;
+                }
+
+                .number-input {
+                    width: 4rem;
+                    background-color: var(--surface);
+                    color: var(--text);
+                    border: 1px solid var(--border);
+                    border-radius: 0.25rem;
+                    padding: 0.125rem 0.375rem;
                 }
 
-                .group-content:focus {
-                    background-color: #1f2937;
-                    box-shadow: inset 0 0 0 2px #3b82f6;
+                @media (prefers-reduced-motion: reduce) {
+                    .main-input {
+                        transition: none;
+                    }
                 }
 
                 @media (max-width: 768px) {
                     .groups-grid {
                         grid-template-columns: 1fr;
                     }
+                    .toolbar-menu-button {
+                        display: block;
+                    }
+                    .toolbar-actions {
+                        display: none;
+                        position: absolute;
+                        top: 100%;
+                        right: 0;
+                        z-index: 10;
+                        flex-direction: column;
+                        margin-top: 0.25rem;
+                        padding: 0.5rem;
+                        background-color: var(--surface-sunken);
+                        border: 1px solid var(--border);
+                        border-radius: 0.375rem;
+                        box-shadow: 0 4px 8px rgba(0, 0, 0, 0.3);
+                    }
+                    .toolbar-actions.open {
+                        display: flex;
+                    }
                     .app-container {
                         padding: 1rem;
                     }
@@ -350,39 +4579,1000 @@ fn App() -> Element {
         }
 
         div {
-            class: "app-container",
+            class: "app-container {settings().theme.class()}",
+            style: "--indent: {settings().indentation.css_width()}",
+
+            if let Some(message) = toast() {
+                div { class: "toast", role: "status", "{message}" }
+            }
+
+            if show_onboarding() {
+                div {
+                    class: "onboarding-overlay",
+                    div {
+                        class: "onboarding-card",
+                        h2 { "Welcome to the APL formatter" }
+                        ul {
+                            li { "Paste a SimC warrior APL into the editor; each action list becomes a card." }
+                            li { "Toggle Humanize to turn SimC expressions into readable phrases." }
+                            li { "Use Copy all, the export buttons or Download to take the result with you." }
+                            li { "No APL handy? Pick one from Load example…" }
+                        }
+                        button {
+                            onclick: move |_| {
+                                save_to_storage(ONBOARDING_STORAGE_KEY, "1");
+                                show_onboarding.set(false);
+                            },
+                            "Got it"
+                        }
+                    }
+                }
+            }
+
+            if show_qr() {
+                div {
+                    class: "onboarding-overlay",
+                    onclick: move |_| show_qr.set(false),
+                    div {
+                        class: "onboarding-card qr-card",
+                        onclick: move |e| e.stop_propagation(),
+                        h2 { "Share link" }
+                        if let Some(code) = qr::QrCode::encode(&link) {
+                            svg {
+                                class: "qr-code",
+                                view_box: "0 0 {code.size + 8} {code.size + 8}",
+                                rect { width: "100%", height: "100%", fill: "#ffffff" }
+                                path { d: code.svg_path(), fill: "#000000" }
+                            }
+                        } else {
+                            p { "This APL is too long for a QR code. Use Copy share link instead." }
+                        }
+                        button { onclick: move |_| show_qr.set(false), "Close" }
+                    }
+                }
+            }
+
+            div {
+                class: "tab-bar",
+                for (i, profile) in profiles().into_iter().enumerate() {
+                    div {
+                        key: "{i}",
+                        class: if i == active() { "tab active" } else { "tab" },
+                        onclick: move |_| {
+                            if active() != i {
+                                previous_groups.set(None);
+                            }
+                            active.set(i);
+                        },
+                        ondoubleclick: move |_| renaming.set(Some(i)),
+                        if renaming() == Some(i) {
+                            input {
+                                class: "tab-rename",
+                                value: "{profile.name}",
+                                autofocus: true,
+                                onchange: move |e| {
+                                    let name = e.value().trim().to_string();
+                                    if !name.is_empty() {
+                                        profiles.write()[i].name = name;
+                                    }
+                                    renaming.set(None);
+                                },
+                                onblur: move |_| renaming.set(None),
+                            }
+                        } else {
+                            span { "{profile.name}" }
+                        }
+                        button {
+                            class: "tab-close",
+                            title: "Close tab",
+                            onclick: move |e| {
+                                e.stop_propagation();
+                                let remaining = {
+                                    let mut list = profiles.write();
+                                    list.remove(i);
+                                    if list.is_empty() {
+                                        list.push(Profile::new("Profile 1".to_string()));
+                                    }
+                                    list.len()
+                                };
+                                if active() > i || active() >= remaining {
+                                    active.set(active().saturating_sub(1));
+                                }
+                                renaming.set(None);
+                                previous_groups.set(None);
+                            },
+                            "×"
+                        }
+                    }
+                }
+                button {
+                    class: "tab-add",
+                    title: "New tab",
+                    onclick: move |_| {
+                        let count = profiles.read().len();
+                        profiles.write().push(Profile::new(format!("Profile {}", count + 1)));
+                        active.set(count);
+                        previous_groups.set(None);
+                    },
+                    "+"
+                }
+            }
+
+            div {
+                class: "toolbar",
+                button {
+                    class: "primary",
+                    title: "Copy every list, formatted with the current settings",
+                    disabled: is_empty,
+                    onclick: move |_| {
+                        copy_to_clipboard(&all_text);
+                        let lists = if list_count == 1 { "1 list".to_string() } else { format!("{} lists", list_count) };
+                        show_toast(toast, format!("Copied {} to the clipboard", lists));
+                    },
+                    "Copy all"
+                }
+                button {
+                    class: "toolbar-menu-button",
+                    onclick: move |_| menu_open.toggle(),
+                    if menu_open() { "✕ Close" } else { "☰ Menu" }
+                }
+                div {
+                    class: if menu_open() { "toolbar-actions open" } else { "toolbar-actions" },
+                    button {
+                        onclick: move |_| {
+                            copy_to_clipboard(&all_markdown);
+                            menu_open.set(false);
+                        },
+                        "Copy as Markdown"
+                    }
+                    button {
+                        onclick: move |_| {
+                            copy_to_clipboard(&all_csv);
+                            menu_open.set(false);
+                        },
+                        "Copy as CSV"
+                    }
+                    button {
+                        onclick: move |_| {
+                            copy_to_clipboard(&all_code_fence);
+                            menu_open.set(false);
+                        },
+                        "Copy as code block"
+                    }
+                    button {
+                        onclick: move |_| {
+                            copy_to_clipboard(&all_outline);
+                            menu_open.set(false);
+                        },
+                        "Copy as outline"
+                    }
+                    button {
+                        onclick: {
+                            let link = link.clone();
+                            move |_| {
+                                copy_to_clipboard(&link);
+                                menu_open.set(false);
+                            }
+                        },
+                        "Copy share link"
+                    }
+                    button {
+                        onclick: move |_| {
+                            show_qr.set(true);
+                            menu_open.set(false);
+                        },
+                        "Show QR"
+                    }
+                    button {
+                        onclick: move |_| {
+                            download_text("apl-formatted.txt", &download_all_text);
+                            menu_open.set(false);
+                        },
+                        "Download"
+                    }
+                    button {
+                        title: "Download the input together with the current settings",
+                        onclick: {
+                            let input = input.clone();
+                            move |_| {
+                                download_text("apl-bundle.json", &bundle_json(&input, &settings()));
+                                menu_open.set(false);
+                            }
+                        },
+                        "Export bundle"
+                    }
+                    button {
+                        title: "Restore the input and settings from an exported bundle",
+                        onclick: move |_| {
+                            menu_open.set(false);
+                            spawn(async move {
+                                let Some(json) = pick_text_file().await else {
+                                    return;
+                                };
+                                match parse_bundle(&json) {
+                                    Ok(bundle) => {
+                                        if let Some(profile) = profiles.write().get_mut(active()) {
+                                            profile.input = bundle.input;
+                                        }
+                                        previous_groups.set(None);
+                                        settings.set(bundle.settings);
+                                        show_toast(toast, "Bundle imported".to_string());
+                                    }
+                                    Err(error) => show_toast(toast, format!("Could not read bundle: {}", error)),
+                                }
+                            });
+                        },
+                        "Import bundle"
+                    }
+                    select {
+                        class: "select-input",
+                        value: "",
+                        onchange: move |e| {
+                            if let Some((_, apl)) = EXAMPLE_APLS.iter().find(|(name, _)| *name == e.value()) {
+                                if let Some(profile) = profiles.write().get_mut(active()) {
+                                    profile.input = apl.to_string();
+                                }
+                                previous_groups.set(None);
+                            }
+                            menu_open.set(false);
+                        },
+                        option { value: "", disabled: true, selected: true, "Load example…" }
+                        for (name, _) in EXAMPLE_APLS {
+                            option { value: *name, "{name}" }
+                        }
+                    }
+                    button {
+                        onclick: move |_| {
+                            if let Some(profile) = profiles.write().get_mut(active()) {
+                                profile.input.clear();
+                            }
+                            previous_groups.set(None);
+                            menu_open.set(false);
+                        },
+                        "Clear"
+                    }
+                    button {
+                        class: if settings().humanize { "active" } else { "" },
+                        onclick: move |_| {
+                            let humanize = !settings().humanize;
+                            settings.write().humanize = humanize;
+                            menu_open.set(false);
+                        },
+                        "Humanize"
+                    }
+                    button {
+                        class: if editor_collapsed() { "active" } else { "" },
+                        onclick: move |_| {
+                            editor_collapsed.toggle();
+                            menu_open.set(false);
+                        },
+                        "Collapse editor"
+                    }
+                    button {
+                        class: if show_glossary() { "active" } else { "" },
+                        onclick: move |_| {
+                            show_glossary.toggle();
+                            menu_open.set(false);
+                        },
+                        "Glossary"
+                    }
+                    button {
+                        class: if show_custom_rules() { "active" } else { "" },
+                        onclick: move |_| {
+                            show_custom_rules.toggle();
+                            menu_open.set(false);
+                        },
+                        "Custom rules"
+                    }
+                }
+            }
+
+            div {
+                class: "find-bar",
+                input {
+                    class: "text-input",
+                    placeholder: "Find",
+                    value: "{find_text}",
+                    oninput: move |e| find_text.set(e.value()),
+                }
+                input {
+                    class: "text-input",
+                    placeholder: "Replace with",
+                    value: "{replace_text}",
+                    oninput: move |e| replace_text.set(e.value()),
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: find_regex(),
+                        onchange: move |e| find_regex.set(e.checked()),
+                    }
+                    "Regex"
+                }
+                button {
+                    disabled: find_error.is_some() || find_text().is_empty(),
+                    onclick: move |_| {
+                        if let Some(profile) = profiles.write().get_mut(active()) {
+                            if let Ok(replaced) = replace_in_apl(
+                                &profile.input,
+                                &find_text(),
+                                &replace_text(),
+                                find_regex(),
+                            ) {
+                                if replaced != profile.input {
+                                    previous_groups.set(Some(parsed.read().0.clone()));
+                                    profile.input = replaced;
+                                }
+                            }
+                        }
+                    },
+                    "Replace all"
+                }
+                if let Some(error) = &find_error {
+                    span { class: "action-error", "⚠ {error}" }
+                }
+            }
+
+            div {
+                class: if editor_collapsed() { "editor collapsed" } else { "editor" },
+                pre {
+                    id: "apl-input-backdrop",
+                    class: "input-backdrop",
+                    aria_hidden: "true",
+                    for (class, text) in highlight_apl(&input, settings().wrap_at_operators && !editing_input()) {
+                        span { class, "{text}" }
+                    }
+                }
+                textarea {
+                    id: "apl-input",
+                    rows: "15",
+                    class: "main-input",
+                    spellcheck: "false",
+                    placeholder: "Paste your APL here...",
+                    value: "{input}",
+                    onfocus: move |_| {
+                        editing_input.set(true);
+                        editor_collapsed.set(false);
+                    },
+                    onblur: move |_| editing_input.set(false),
+                    onpaste: move |_| previous_groups.set(Some(parsed.read().0.clone())),
+                    oninput: move |e| {
+                        if let Some(profile) = profiles.write().get_mut(active()) {
+                            profile.input = e.value();
+                        }
+                    },
+                    onscroll: move |_| {
+                        let _ = document::eval(
+                            "document.getElementById('apl-input-backdrop').scrollTop = document.getElementById('apl-input').scrollTop;",
+                        );
+                    },
+                }
+                if editor_collapsed() {
+                    button {
+                        class: "editor-expand",
+                        onclick: move |_| editor_collapsed.set(false),
+                        "Edit"
+                    }
+                }
+            }
 
-            textarea {
-                rows: "15",
-                class: "main-input",
-                placeholder: "Paste your APL here...",
-                value: "{input()}",
-                oninput: move |e| input.set(e.value()),
+            div {
+                class: "status-line",
+                "{char_count} characters · {action_count} actions · parsed in {parse_ms:.2} ms"
             }
 
             div {
-                class: "groups-grid",
-                for (when_type, spells) in groups.iter() {
-                    {
-                        let numbered_spells = spells.iter()
-                            .enumerate()
-                            .map(|(i, spell)| format!("({}) {}", i + 1, spell))
-                            .collect::<Vec<_>>()
-                            .join("\n\n");
-
-                        rsx! {
+                class: "settings-bar",
+                label {
+                    "Theme"
+                    select {
+                        class: "select-input",
+                        onchange: move |e| {
+                            if let Some(theme) = Theme::ALL.into_iter().find(|t| t.class() == e.value()) {
+                                settings.write().theme = theme;
+                            }
+                        },
+                        for theme in Theme::ALL {
+                            option {
+                                value: theme.class(),
+                                selected: settings().theme == theme,
+                                "{theme.label()}"
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Operators"
+                    select {
+                        class: "select-input",
+                        onchange: move |e| {
+                            if let Some(style) = OperatorStyle::ALL.into_iter().find(|s| s.key() == e.value()) {
+                                settings.write().operator_style = style;
+                            }
+                        },
+                        for style in OperatorStyle::ALL {
+                            option {
+                                value: style.key(),
+                                selected: settings().operator_style == style,
+                                "{style.label()}"
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Compare to"
+                    select {
+                        class: "select-input",
+                        onchange: move |e| {
+                            let name = e.value();
+                            settings.write().compare_reference = EXAMPLE_APLS
+                                .iter()
+                                .find(|(reference, _)| *reference == name)
+                                .map(|(reference, _)| reference.to_string());
+                        },
+                        option {
+                            value: "",
+                            selected: settings().compare_reference.is_none(),
+                            "Last edit"
+                        }
+                        for (name, _) in EXAMPLE_APLS {
+                            option {
+                                value: *name,
+                                selected: settings().compare_reference.as_deref() == Some(*name),
+                                "Reference: {name}"
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Indent"
+                    select {
+                        class: "select-input",
+                        onchange: move |e| {
+                            if let Some(indentation) = Indentation::ALL.into_iter().find(|i| i.key() == e.value()) {
+                                settings.write().indentation = indentation;
+                            }
+                        },
+                        for indentation in Indentation::ALL {
+                            option {
+                                value: indentation.key(),
+                                selected: settings().indentation == indentation,
+                                "{indentation.label()}"
+                            }
+                        }
+                    }
+                }
+                label {
+                    "Comments"
+                    select {
+                        class: "select-input",
+                        onchange: move |e| {
+                            if let Some(mode) = CommentMode::ALL.into_iter().find(|m| m.key() == e.value()) {
+                                settings.write().comment_mode = mode;
+                            }
+                        },
+                        for mode in CommentMode::ALL {
+                            option {
+                                value: mode.key(),
+                                selected: settings().comment_mode == mode,
+                                "{mode.label()}"
+                            }
+                        }
+                    }
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().humanize,
+                        onchange: move |e| settings.write().humanize = e.checked(),
+                    }
+                    "Humanize conditions"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().normalize_whitespace,
+                        onchange: move |e| settings.write().normalize_whitespace = e.checked(),
+                    }
+                    "Normalize pasted whitespace"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().collapse_long_or,
+                        onchange: move |e| settings.write().collapse_long_or = e.checked(),
+                    }
+                    "Collapse OR chains longer than"
+                }
+                input {
+                    r#type: "number",
+                    class: "number-input",
+                    min: "1",
+                    value: "{settings().or_collapse_threshold}",
+                    oninput: move |e| {
+                        if let Ok(n) = e.value().parse::<usize>() {
+                            settings.write().or_collapse_threshold = n.max(1);
+                        }
+                    },
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().collapse_long_conditions,
+                        onchange: move |e| settings.write().collapse_long_conditions = e.checked(),
+                    }
+                    "Collapse conditions longer than"
+                }
+                input {
+                    r#type: "number",
+                    class: "number-input",
+                    min: "1",
+                    value: "{settings().condition_line_limit}",
+                    oninput: move |e| {
+                        if let Ok(n) = e.value().parse::<usize>() {
+                            settings.write().condition_line_limit = n.max(1);
+                        }
+                    },
+                }
+                "lines"
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().highlight_phases,
+                        onchange: move |e| settings.write().highlight_phases = e.checked(),
+                    }
+                    "Tag execute and cooldown phases"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().tree_view,
+                        onchange: move |e| settings.write().tree_view = e.checked(),
+                    }
+                    "Show conditions as a tree"
+                }
+                label {
+                    title: "Keep short OR groups on one line so AND chains wrap horizontally",
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().horizontal_layout,
+                        onchange: move |e| settings.write().horizontal_layout = e.checked(),
+                    }
+                    "Horizontal layout"
+                }
+                label {
+                    title: "Color nested condition blocks by depth, starting from the second level",
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().depth_colors,
+                        onchange: move |e| settings.write().depth_colors = e.checked(),
+                    }
+                    "Color by depth"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().group_by_spell,
+                        onchange: move |e| settings.write().group_by_spell = e.checked(),
+                    }
+                    "Group by spell"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().validate_spells,
+                        onchange: move |e| settings.write().validate_spells = e.checked(),
+                    }
+                    "Flag unknown spells"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().lint_unreachable,
+                        onchange: move |e| settings.write().lint_unreachable = e.checked(),
+                    }
+                    "Flag actions after unconditional ones"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().effective_conditions,
+                        onchange: move |e| settings.write().effective_conditions = e.checked(),
+                    }
+                    "Show list entry conditions"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().merge_shared_conditions,
+                        onchange: move |e| settings.write().merge_shared_conditions = e.checked(),
+                    }
+                    "Merge shared conditions"
+                }
+                label {
+                    title: "Removes repeated and absorbed terms by exact text match only",
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().simplify_conditions,
+                        onchange: move |e| settings.write().simplify_conditions = e.checked(),
+                    }
+                    "Simplify conditions"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().show_rage_costs,
+                        onchange: move |e| settings.write().show_rage_costs = e.checked(),
+                    }
+                    "Show rage costs"
+                }
+                label {
+                    title: "Wrap long input lines after & and | while the editor is not focused",
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().wrap_at_operators,
+                        onchange: move |e| settings.write().wrap_at_operators = e.checked(),
+                    }
+                    "Wrap input at operators"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().continuous_numbering,
+                        onchange: move |e| settings.write().continuous_numbering = e.checked(),
+                    }
+                    "Number across lists"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: settings().ladder_view,
+                        onchange: move |e| settings.write().ladder_view = e.checked(),
+                    }
+                    "Priority ladder"
+                }
+            }
+
+            if show_custom_rules() {
+                div {
+                    class: "gear-panel",
+                    h3 { class: "group-header", "Custom humanization rules" }
+                    div {
+                        class: "group-content",
+                        p {
+                            class: "action-annotation",
+                            "One rule per line as `pattern => replacement`. Patterns are regexes matched against whole tokens and take priority over the built-in rules while Humanize is on; use $1 for capture groups."
+                        }
+                        textarea {
+                            class: "text-input custom-rules-input",
+                            rows: "5",
+                            spellcheck: "false",
+                            placeholder: "buff\\.([\\w']+)\\.up => $1 active",
+                            value: "{settings().custom_rules.source}",
+                            oninput: move |e| settings.write().custom_rules = e.value().into(),
+                        }
+                        for error in settings().custom_rules.errors {
+                            div { class: "action-error", "⚠ {error}" }
+                        }
+                    }
+                }
+            }
+
+            if show_glossary() {
+                div {
+                    class: "gear-panel",
+                    h3 { class: "group-header", "Glossary" }
+                    div {
+                        class: "group-content",
+                        input {
+                            class: "text-input",
+                            placeholder: "Search tokens or phrases",
+                            value: "{glossary_filter}",
+                            oninput: move |e| glossary_filter.set(e.value()),
+                        }
+                        div {
+                            class: "profile-settings",
+                            for (pattern, rendering) in glossary_entries().into_iter().filter(|(pattern, rendering)| {
+                                let filter = glossary_filter().to_lowercase();
+                                pattern.to_lowercase().contains(&filter) || rendering.to_lowercase().contains(&filter)
+                            }) {
+                                code { "{pattern}" }
+                                span { "{rendering}" }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !pinned_actions.is_empty() {
+                div {
+                    class: "pinned-panel",
+                    h3 { class: "group-header", "Pinned" }
+                    div {
+                        class: "group-content",
+                        for (group, index, action) in pinned_actions {
+                            div {
+                                key: "{group}-{index}",
+                                class: "pinned-group",
+                                "{group}"
+                            }
+                            {
+                                let key = action_key(&profile_name, &group, &action);
+                                rsx! {
+                                    ActionView {
+                                        number: numbers_by_group[&group][index],
+                                        note: notes.read().get(&key).cloned().unwrap_or_default(),
+                                        on_note: {
+                                            let key = key.clone();
+                                            move |text| set_note(notes, &key, text)
+                                        },
+                                        action,
+                                        change: ChangeKind::Unchanged,
+                                        settings: settings(),
+                                        pinned: true,
+                                        entered_from: entry_conditions.get(&group).cloned().unwrap_or_default(),
+                                        unreachable_after: None,
+                                        merged: false,
+                                        on_pin: move |_| toggle_pin(pins, &key),
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !gear.is_empty() {
+                div {
+                    class: "gear-panel",
+                    h3 { class: "group-header", "Gear dependencies" }
+                    div {
+                        class: "group-content",
+                        for token in gear {
+                            div {
+                                key: "{token}",
+                                class: "gear-item",
+                                code { "{token}" }
+                                if let Some(phrase) = humanize_token(&token) {
+                                    span { class: "action-annotation", "{phrase}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !profile_values.is_empty() {
+                div {
+                    class: "gear-panel",
+                    h3 { class: "group-header", "Profile settings" }
+                    div {
+                        class: "group-content profile-settings",
+                        for (key, value) in profile_values {
+                            span { class: "hl-keyword", "{key}" }
+                            code { "{value}" }
+                        }
+                    }
+                }
+            }
+
+            if is_empty {
+                div {
+                    class: "empty-state",
+                    if input_is_blank {
+                        p { "Paste a SimC APL above to see each action list formatted here." }
+                    } else {
+                        p { "No actions found. Action lines look like actions+=/spell,if=condition." }
+                    }
+                    div {
+                        class: "empty-state-examples",
+                        "Or start from an example:"
+                        for (name, apl) in EXAMPLE_APLS {
+                            button {
+                                onclick: move |_| {
+                                    if let Some(profile) = profiles.write().get_mut(active()) {
+                                        profile.input = apl.to_string();
+                                    }
+                                    previous_groups.set(None);
+                                },
+                                "{name}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(spell_groups) = spell_groups {
+                div {
+                    class: "groups-grid",
+                    for (spell, usages) in spell_groups {
+                        div {
+                            key: "{spell}",
+                            class: "group-card",
+                            h3 { class: "group-header", "{spell}" }
                             div {
-                                key: "{when_type}",
-                                class: "group-card",
-                                h3 {
-                                    class: "group-header",
-                                    "{when_type}"
+                                class: "group-content",
+                                for (group, index, action) in usages {
+                                    div {
+                                        key: "{group}-{index}",
+                                        class: "pinned-group",
+                                        "{group}"
+                                    }
+                                    {
+                                        let key = action_key(&profile_name, &group, &action);
+                                        rsx! {
+                                            ActionView {
+                                                number: numbers_by_group[&group][index],
+                                                note: notes.read().get(&key).cloned().unwrap_or_default(),
+                                                on_note: {
+                                                    let key = key.clone();
+                                                    move |text| set_note(notes, &key, text)
+                                                },
+                                                action,
+                                                change: ChangeKind::Unchanged,
+                                                settings: settings(),
+                                                pinned: pins.read().contains(&key),
+                                                entered_from: entry_conditions.get(&group).cloned().unwrap_or_default(),
+                                                unreachable_after: None,
+                                                merged: false,
+                                                on_pin: move |_| toggle_pin(pins, &key),
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            } else if settings().ladder_view {
+                div {
+                    class: "groups-grid",
+                    for (when_type, spells) in groups.iter() {
+                        div {
+                            key: "{when_type}",
+                            class: "group-card",
+                            h3 { class: "group-header", "{when_type}" }
+                            ol {
+                                class: "ladder",
+                                for action in spells.iter() {
+                                    li {
+                                        class: "ladder-rung",
+                                        span { class: "action-spell", {display_spell(&action.spell)} }
+                                        if let Some(condition) = &action.condition {
+                                            span {
+                                                class: "ladder-summary",
+                                                title: "{pretty_format_condition(condition, 0, settings().operator_style, settings().indentation)}",
+                                                {condition_summary(condition, 3, 80)}
+                                            }
+                                        }
+                                    }
                                 }
-                                textarea {
-                                    rows: "30",
-                                    class: "group-content",
-                                    readonly: true,
-                                    value: "{numbered_spells}",
+                            }
+                        }
+                    }
+                }
+            } else {
+                div {
+                    class: "groups-grid",
+                    onkeydown: move |e| {
+                        if let Some((group, index)) = move_focus(focused(), &group_sizes, &e.key()) {
+                            e.prevent_default();
+                            focused.set(Some((group, index)));
+                            focus_element(&format!("action-{}-{}", group, index));
+                        }
+                    },
+                    for (group_index, (when_type, spells)) in groups.into_iter().enumerate() {
+                        {
+                            let numbers = numbers_by_group[&when_type].clone();
+                            let group_text = format_group(&spells, &numbers, &settings());
+                            let group_simc = format_group_simc(&when_type, &spells);
+                            let group_csv = format_group_csv(&spells, &settings());
+                            let group_fence = format_group_code_fence(&when_type, &spells, &settings());
+                            let (changes, removed) = match diff_base.get(&when_type) {
+                                _ if diff_base.is_empty() => (vec![ChangeKind::Unchanged; spells.len()], Vec::new()),
+                                Some(previous) => diff_actions(previous, &spells),
+                                None => (vec![ChangeKind::Added; spells.len()], Vec::new()),
+                            };
+                            let blockers = if settings().lint_unreachable {
+                                unreachable_after(&spells)
+                            } else {
+                                vec![None; spells.len()]
+                            };
+                            let mut run_starts = vec![None; spells.len()];
+                            let mut in_run = vec![false; spells.len()];
+                            if settings().merge_shared_conditions {
+                                for (start, len) in shared_condition_runs(&spells) {
+                                    run_starts[start] = Some(len);
+                                    in_run[start..start + len].fill(true);
+                                }
+                            }
+
+                            rsx! {
+                                div {
+                                    key: "{when_type}",
+                                    class: "group-card",
+                                    h3 {
+                                        class: "group-header",
+                                        "{when_type}"
+                                        button {
+                                            class: "copy-button",
+                                            title: "Copy group",
+                                            onclick: move |_| copy_to_clipboard(&group_text),
+                                            "Copy"
+                                        }
+                                        button {
+                                            class: "copy-button",
+                                            title: "Copy group as SimC",
+                                            onclick: move |_| copy_to_clipboard(&group_simc),
+                                            "SimC"
+                                        }
+                                        button {
+                                            class: "copy-button",
+                                            title: "Copy group as CSV",
+                                            onclick: move |_| copy_to_clipboard(&group_csv),
+                                            "CSV"
+                                        }
+                                        button {
+                                            class: "copy-button",
+                                            title: "Copy group as a Markdown code block",
+                                            onclick: move |_| copy_to_clipboard(&group_fence),
+                                            "Code block"
+                                        }
+                                    }
+                                    div {
+                                        class: "group-content",
+                                        for (i, (action, change)) in spells.into_iter().zip(changes).enumerate() {
+                                            if let (Some(len), Some(condition)) = (run_starts[i], action.condition.clone()) {
+                                                div { class: "cond-label shared-condition", "the next {len} actions share:" }
+                                                div {
+                                                    class: "cond-block shared-condition",
+                                                    ConditionView {
+                                                        expr: condition,
+                                                        nested: false,
+                                                        collapse_over: settings().or_collapse_over(),
+                                                        style: settings().operator_style,
+                                                        horizontal: settings().horizontal_layout,
+                                                        depth: 0,
+                                                        depth_colors: settings().depth_colors,
+                                                    }
+                                                }
+                                            }
+                                            div {
+                                                id: "action-{group_index}-{i}",
+                                                class: "action-focus",
+                                                tabindex: "0",
+                                                onfocus: move |_| focused.set(Some((group_index, i))),
+                                                {
+                                                    let key = action_key(&profile_name, &when_type, &action);
+                                                    rsx! {
+                                                        ActionView {
+                                                            number: numbers[i],
+                                                            note: notes.read().get(&key).cloned().unwrap_or_default(),
+                                                            on_note: {
+                                                                let key = key.clone();
+                                                                move |text| set_note(notes, &key, text)
+                                                            },
+                                                            action,
+                                                            change,
+                                                            settings: settings(),
+                                                            pinned: pins.read().contains(&key),
+                                                            entered_from: entry_conditions.get(&when_type).cloned().unwrap_or_default(),
+                                                            unreachable_after: blockers[i].clone(),
+                                                            merged: in_run[i],
+                                                            on_pin: move |_| toggle_pin(pins, &key),
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        for spell in removed {
+                                            div {
+                                                class: "action removed",
+                                                title: if settings().compare_reference.is_some() { "Missing from the reference APL" } else { "Removed since last edit" },
+                                                "{spell}"
+                                            }
+                                        }
+                                    }
                                 }
                             }
                         }
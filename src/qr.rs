@@ -0,0 +1,581 @@
+// Minimal QR code encoder (byte mode, error correction level M with a fallback to L) for
+// rendering share links. Follows the layout of ISO/IEC 18004; tables are indexed by version.
+
+const ECC_CODEWORDS_PER_BLOCK: [[usize; 41]; 2] = [
+    [
+        0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28,
+        30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28,
+        28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    ],
+];
+
+const ERROR_CORRECTION_BLOCKS: [[usize; 41]; 2] = [
+    [
+        0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13,
+        14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25,
+    ],
+    [
+        0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21,
+        23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+    ],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ecc {
+    Low,
+    Medium,
+}
+
+impl Ecc {
+    fn index(self) -> usize {
+        match self {
+            Ecc::Low => 0,
+            Ecc::Medium => 1,
+        }
+    }
+
+    fn format_bits(self) -> u32 {
+        match self {
+            Ecc::Low => 1,
+            Ecc::Medium => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QrCode {
+    pub size: usize,
+    modules: Vec<bool>,
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes `text` as UTF-8 bytes, or returns `None` when it exceeds the capacity of a
+    /// version 40 symbol.
+    pub fn encode(text: &str) -> Option<QrCode> {
+        let data = text.as_bytes();
+        let (version, ecc) = [Ecc::Medium, Ecc::Low].into_iter().find_map(|ecc| {
+            (1..=40)
+                .find(|&version| {
+                    data_bits_needed(data.len(), version) <= data_codewords(version, ecc) * 8
+                })
+                .map(|version| (version, ecc))
+        })?;
+
+        let codewords = add_error_correction(&data_codewords_for(data, version, ecc), version, ecc);
+        let size = version * 4 + 17;
+        let mut qr = QrCode {
+            size,
+            modules: vec![false; size * size],
+            function: vec![false; size * size],
+        };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&codewords);
+
+        let mut best = None;
+        for mask in 0..8 {
+            let mut candidate = qr.clone();
+            candidate.apply_mask(mask);
+            candidate.draw_format_bits(ecc, mask);
+            let penalty = candidate.penalty();
+            if best.as_ref().is_none_or(|(score, _)| penalty < *score) {
+                best = Some((penalty, candidate));
+            }
+        }
+        best.map(|(_, qr)| qr)
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    /// One `h1v1` square per dark module, offset by a four-module quiet zone; meant for a
+    /// `viewBox` of `0 0 (size + 8) (size + 8)`.
+    pub fn svg_path(&self) -> String {
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.is_dark(x, y) {
+                    path.push_str(&format!("M{},{}h1v1h-1z", x + 4, y + 4));
+                }
+            }
+        }
+        path
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        for i in 0..self.size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        self.draw_finder(3, 3);
+        self.draw_finder(self.size - 4, 3);
+        self.draw_finder(3, self.size - 4);
+
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                let on_finder = (i == 0 && (j == 0 || j == last)) || (i == last && j == 0);
+                if !on_finder {
+                    self.draw_alignment(x, y);
+                }
+            }
+        }
+
+        self.draw_format_bits(Ecc::Medium, 0);
+        self.draw_version(version);
+    }
+
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (xx, yy) = (x as i32 + dx, y as i32 + dy);
+                if (0..self.size as i32).contains(&xx) && (0..self.size as i32).contains(&yy) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2i32..=2 {
+            for dx in -2i32..=2 {
+                let dark = dx.abs().max(dy.abs()) != 1;
+                self.set_function((x as i32 + dx) as usize, (y as i32 + dy) as usize, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, ecc: Ecc, mask: u32) {
+        let data = (ecc.format_bits() << 3) | mask;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | remainder) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        let size = self.size;
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+        let mut remainder = version as u32;
+        for _ in 0..12 {
+            remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+        }
+        let bits = ((version as u32) << 12) | remainder;
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 != 0;
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let mut bit = 0;
+        let mut right = self.size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..self.size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        self.size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.function[y * self.size + x] && bit < codewords.len() * 8 {
+                        self.modules[y * self.size + x] =
+                            (codewords[bit >> 3] >> (7 - (bit & 7))) & 1 != 0;
+                        bit += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if invert && !self.function[y * self.size + x] {
+                    self.modules[y * self.size + x] ^= true;
+                }
+            }
+        }
+    }
+
+    fn penalty(&self) -> usize {
+        const FINDER_LIKE: [bool; 11] = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        let size = self.size;
+        let lines = (0..size)
+            .map(|y| (0..size).map(|x| self.is_dark(x, y)).collect::<Vec<_>>())
+            .chain((0..size).map(|x| (0..size).map(|y| self.is_dark(x, y)).collect::<Vec<_>>()));
+
+        let mut score = 0;
+        for line in lines {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    score += run - 2;
+                }
+                run = 1;
+            }
+            for window in line.windows(FINDER_LIKE.len()) {
+                if window == FINDER_LIKE || window.iter().rev().eq(FINDER_LIKE.iter()) {
+                    score += 40;
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let color = self.is_dark(x, y);
+                if color == self.is_dark(x + 1, y)
+                    && color == self.is_dark(x, y + 1)
+                    && color == self.is_dark(x + 1, y + 1)
+                {
+                    score += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&m| m).count();
+        let deviation = (dark * 20).abs_diff(total * 10);
+        score + deviation.div_ceil(total).saturating_sub(1) * 10
+    }
+}
+
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2;
+    let size = version * 4 + 17;
+    let mut positions: Vec<usize> = (0..count - 1).map(|i| size - 7 - i * step).collect();
+    positions.push(6);
+    positions.reverse();
+    positions
+}
+
+fn raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        result -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(version: usize, ecc: Ecc) -> usize {
+    raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[ecc.index()][version]
+            * ERROR_CORRECTION_BLOCKS[ecc.index()][version]
+}
+
+fn count_bits(version: usize) -> usize {
+    if version <= 9 {
+        8
+    } else {
+        16
+    }
+}
+
+fn data_bits_needed(bytes: usize, version: usize) -> usize {
+    if bytes >= 1 << count_bits(version) {
+        return usize::MAX;
+    }
+    4 + count_bits(version) + bytes * 8
+}
+
+fn data_codewords_for(data: &[u8], version: usize, ecc: Ecc) -> Vec<u8> {
+    let mut bits: Vec<bool> = Vec::new();
+    let mut push = |value: usize, len: usize| {
+        bits.extend((0..len).rev().map(|i| (value >> i) & 1 != 0));
+    };
+    push(0b0100, 4);
+    push(data.len(), count_bits(version));
+    for &byte in data {
+        push(byte as usize, 8);
+    }
+
+    let capacity = data_codewords(version, ecc) * 8;
+    let terminator = (capacity - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator));
+    bits.extend(std::iter::repeat_n(false, (8 - bits.len() % 8) % 8));
+
+    let mut codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0u8, |byte, &bit| (byte << 1) | bit as u8))
+        .collect();
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if codewords.len() * 8 >= capacity {
+            break;
+        }
+        codewords.push(pad);
+    }
+    codewords
+}
+
+fn add_error_correction(data: &[u8], version: usize, ecc: Ecc) -> Vec<u8> {
+    let blocks_count = ERROR_CORRECTION_BLOCKS[ecc.index()][version];
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[ecc.index()][version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = blocks_count - raw_codewords % blocks_count;
+    let short_len = raw_codewords / blocks_count;
+
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    for i in 0..blocks_count {
+        let data_len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let mut block = data[offset..offset + data_len].to_vec();
+        offset += data_len;
+        let remainder = reed_solomon_remainder(&block, &divisor);
+        if i < short_blocks {
+            block.push(0);
+        }
+        block.extend(remainder);
+        blocks.push(block);
+    }
+
+    let mut result = Vec::new();
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            if i != short_len - ecc_len || j >= short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u8 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1D);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree - 1];
+    result.push(1);
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (value, &coefficient) in result.iter_mut().zip(divisor) {
+            *value ^= gf_multiply(coefficient, factor);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syndromes_vanish(codeword: &[u8], ecc_len: usize) -> bool {
+        let mut root = 1u8;
+        (0..ecc_len).all(|_| {
+            let value = codeword
+                .iter()
+                .fold(0u8, |acc, &byte| gf_multiply(acc, root) ^ byte);
+            root = gf_multiply(root, 0x02);
+            value == 0
+        })
+    }
+
+    fn format_bits(qr: &QrCode) -> u32 {
+        (0..15).fold(0, |bits, i| {
+            let (x, y) = if i < 8 {
+                (qr.size - 1 - i, 8)
+            } else {
+                (8, qr.size - 15 + i)
+            };
+            bits | (u32::from(qr.is_dark(x, y)) << i)
+        })
+    }
+
+    fn read_codewords(qr: &QrCode) -> Vec<u8> {
+        let mut unmasked = qr.clone();
+        unmasked.apply_mask(((format_bits(qr) ^ 0x5412) >> 10) & 7);
+        let mut bits = Vec::new();
+        let mut right = qr.size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..qr.size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let y = if (right + 1) & 2 == 0 {
+                        qr.size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !qr.function[y * qr.size + x] {
+                        bits.push(unmasked.is_dark(x, y));
+                    }
+                }
+            }
+            right -= 2;
+        }
+        bits.chunks_exact(8)
+            .map(|chunk| chunk.iter().fold(0u8, |byte, &bit| (byte << 1) | bit as u8))
+            .collect()
+    }
+
+    #[test]
+    fn capacities_match_the_standard() {
+        assert_eq!(data_codewords(1, Ecc::Low), 19);
+        assert_eq!(data_codewords(1, Ecc::Medium), 16);
+        assert_eq!(data_codewords(10, Ecc::Medium), 216);
+        assert_eq!(data_codewords(40, Ecc::Low), 2956);
+        assert_eq!(data_codewords(40, Ecc::Medium), 2334);
+    }
+
+    #[test]
+    fn interleaved_blocks_carry_valid_error_correction() {
+        // Version 10-M mixes four 69-codeword blocks with one 70-codeword block.
+        let version = 10;
+        let data = data_codewords_for(&[b'a'; 200], version, Ecc::Medium);
+        let interleaved = add_error_correction(&data, version, Ecc::Medium);
+        let ecc_len = ECC_CODEWORDS_PER_BLOCK[1][version];
+        let (short_blocks, short_len) = (4, 69);
+        let mut blocks = vec![Vec::new(); 5];
+        let mut next = interleaved.iter();
+        for i in 0..=short_len {
+            for (j, block) in blocks.iter_mut().enumerate() {
+                if i != short_len - ecc_len || j >= short_blocks {
+                    block.push(*next.next().unwrap());
+                }
+            }
+        }
+        let mut recovered = Vec::new();
+        for block in &blocks {
+            assert!(syndromes_vanish(block, ecc_len));
+            recovered.extend_from_slice(&block[..block.len() - ecc_len]);
+        }
+        assert_eq!(recovered, data);
+    }
+
+    #[test]
+    fn symbol_has_finders_and_matching_format_copies() {
+        let qr = QrCode::encode("https://example.com/#apl=actions%3Dcharge").unwrap();
+        assert_eq!(qr.size, 29);
+        for (x, y) in [(0, 0), (qr.size - 7, 0), (0, qr.size - 7)] {
+            assert!(qr.is_dark(x, y) && qr.is_dark(x + 6, y + 6));
+            assert!(!qr.is_dark(x + 1, y + 1) && qr.is_dark(x + 2, y + 2));
+        }
+        assert!(qr.is_dark(8, qr.size - 8));
+
+        let first: u32 = (0..15).fold(0, |bits, i| {
+            let (x, y) = match i {
+                0..=5 => (8, i),
+                6 => (8, 7),
+                7 => (8, 8),
+                8 => (7, 8),
+                _ => (14 - i, 8),
+            };
+            bits | (u32::from(qr.is_dark(x, y)) << i)
+        });
+        assert_eq!(first, format_bits(&qr));
+        assert_eq!((first ^ 0x5412) >> 13, Ecc::Medium.format_bits());
+    }
+
+    #[test]
+    fn modules_read_back_as_the_encoded_codewords() {
+        for (text, version) in [
+            ("https://example.com/#apl=x", 2),
+            ("ab".repeat(100).as_str(), 10),
+        ] {
+            let qr = QrCode::encode(text).unwrap();
+            assert_eq!(qr.size, version * 4 + 17);
+            let expected = add_error_correction(
+                &data_codewords_for(text.as_bytes(), version, Ecc::Medium),
+                version,
+                Ecc::Medium,
+            );
+            assert_eq!(read_codewords(&qr)[..expected.len()], expected[..]);
+        }
+    }
+
+    #[test]
+    fn long_links_pick_larger_versions_until_they_no_longer_fit() {
+        assert!(QrCode::encode(&"a".repeat(1200)).unwrap().size > 100);
+        assert!(QrCode::encode(&"a".repeat(3000)).is_none());
+    }
+}
@@ -0,0 +1,139 @@
+use super::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+type Configure = fn(&mut Settings);
+
+// Each fixture in tests/fixtures/<name>.simc is formatted with the settings below and compared
+// against tests/snapshots/<name>.txt. Run with UPDATE_SNAPSHOTS=1 to rewrite the expected files.
+const FIXTURES: &[(&str, Configure)] = &[
+    ("arms_single_target", |_| {}),
+    ("fury_aoe_humanized", |settings| settings.humanize = true),
+    ("multi_profile", |_| {}),
+    ("comments_inline", |settings| {
+        settings.comment_mode = CommentMode::Inline
+    }),
+    ("variables_and_waits", |_| {}),
+    ("negation_and_nesting", |settings| {
+        settings.operator_style = OperatorStyle::Symbols;
+        settings.indentation = Indentation::TwoSpaces;
+        settings.humanize = true;
+    }),
+    ("profile_settings", |settings| settings.humanize = true),
+    ("weapon_gates", |settings| settings.humanize = true),
+    ("nested_modifier_commas", |_| {}),
+    ("cooldown_alignment", |settings| settings.humanize = true),
+    ("max_stacks", |settings| settings.humanize = true),
+    ("wrapped_lines", |settings| {
+        settings.normalize_whitespace = true
+    }),
+];
+
+fn snapshot(apl: &str, settings: &Settings) -> String {
+    let mut output = format_groups(&process_apl_grouped(apl, settings), settings);
+    let profile_values = profile_settings(apl);
+    if !profile_values.is_empty() {
+        output.push_str("\n\nprofile settings");
+        for (key, value) in profile_values {
+            output.push_str(&format!("\n{} = {}", key, value));
+        }
+    }
+    output.push('\n');
+    output
+}
+
+fn check_snapshot(
+    root: &Path,
+    name: &str,
+    actual: &str,
+    update: bool,
+    mismatches: &mut Vec<String>,
+) {
+    let path = root.join("snapshots").join(format!("{}.txt", name));
+    if update {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    if expected != actual {
+        mismatches.push(format!(
+            "--- {} (expected)\n{}\n--- {} (actual)\n{}",
+            name, expected, name, actual
+        ));
+    }
+}
+
+fn load_fixture(root: &Path, name: &str) -> String {
+    fs::read_to_string(root.join("fixtures").join(format!("{}.simc", name)))
+        .unwrap_or_else(|e| panic!("missing fixture {}: {}", name, e))
+}
+
+fn assert_no_mismatches(mismatches: Vec<String>) {
+    assert!(
+        mismatches.is_empty(),
+        "{} snapshot(s) differ; rerun with UPDATE_SNAPSHOTS=1 if the change is intended\n\n{}",
+        mismatches.len(),
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn formatted_output_matches_snapshots() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let mut mismatches = Vec::new();
+
+    for (name, configure) in FIXTURES {
+        let apl = load_fixture(&root, name);
+        let mut settings = Settings::default();
+        configure(&mut settings);
+        check_snapshot(
+            &root,
+            name,
+            &snapshot(&apl, &settings),
+            update,
+            &mut mismatches,
+        );
+    }
+
+    assert_no_mismatches(mismatches);
+}
+
+#[test]
+fn code_fence_copy_matches_snapshot() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let mut mismatches = Vec::new();
+
+    let settings = Settings {
+        indentation: Indentation::Tab,
+        comment_mode: CommentMode::Inline,
+        ..Settings::default()
+    };
+    let apl = load_fixture(&root, "comments_inline") + &load_fixture(&root, "negation_and_nesting");
+    let groups = process_apl_grouped(&apl, &settings);
+    let actual = format_groups_code_fence(&groups, &settings) + "\n";
+    check_snapshot(&root, "code_fence", &actual, update, &mut mismatches);
+
+    assert_no_mismatches(mismatches);
+}
+
+#[test]
+fn root_indicators_match_snapshot() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let mut mismatches = Vec::new();
+
+    let groups = process_apl_grouped(
+        &load_fixture(&root, "root_indicators"),
+        &Settings::default(),
+    );
+    let mut actual = String::new();
+    for action in groups.values().flatten() {
+        let kind = action.condition.as_ref().map_or("none", root_kind);
+        actual.push_str(&format!("{}: {}\n", action.spell, kind));
+    }
+    check_snapshot(&root, "root_indicators", &actual, update, &mut mismatches);
+
+    assert_no_mismatches(mismatches);
+}
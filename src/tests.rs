@@ -0,0 +1,1198 @@
+use super::*;
+
+fn humanized(raw: &str) -> String {
+    let settings = Settings {
+        humanize: true,
+        ..Settings::default()
+    };
+    pretty_format_condition(
+        &transform_condition(raw, &settings),
+        0,
+        OperatorStyle::Words,
+        Indentation::FourSpaces,
+    )
+}
+
+#[test]
+fn long_atoms_wrap_only_at_arithmetic_operators_on_their_own_line() {
+    let chain = (1..=20)
+        .map(|i| format!("buff.stack_{i}.value"))
+        .collect::<Vec<_>>()
+        .join("+");
+    let formatted = pretty_format_condition(
+        &parse_simc_condition(&format!("{chain}>10")),
+        1,
+        OperatorStyle::Words,
+        Indentation::TwoSpaces,
+    );
+    assert!(formatted.lines().count() > 1);
+    assert!(formatted
+        .lines()
+        .all(|line| line.chars().count() <= ATOM_WRAP_WIDTH));
+    assert!(formatted
+        .lines()
+        .skip(1)
+        .all(|line| line.starts_with("    ")));
+
+    let formatted = pretty_format_condition(
+        &parse_simc_condition(
+            "dot.deep_wounds.remains<dot.deep_wounds.duration*0.3&active_enemies>=2&active_dot.deep_wounds<active_enemies",
+        ),
+        1,
+        OperatorStyle::Words,
+        Indentation::TwoSpaces,
+    );
+    assert_eq!(
+        formatted,
+        "  dot.deep_wounds.remains < dot.deep_wounds.duration*0.3 AND active_enemies >= 2\n    AND active_dot.deep_wounds < active_enemies"
+    );
+}
+
+#[test]
+fn degenerate_lines_produce_no_groups_or_empty_cards() {
+    let settings = Settings::default();
+    for line in [
+        "=",
+        "actions=",
+        "actions+=/",
+        "actions=,if=rage>40",
+        "=mortal_strike",
+    ] {
+        assert_eq!(process_line(line, &settings), None, "{line}");
+    }
+    let groups = process_apl_grouped("=\nactions=\nactions+=/execute", &settings);
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups["actions"].len(), 1);
+    assert_eq!(groups["actions"][0].spell, "execute");
+}
+
+#[test]
+fn interrupt_if_and_chain_are_split_out_of_the_spell() {
+    let settings = Settings::default();
+    let (when, action) = process_line(
+        "actions+=/bladestorm,interrupt_if=rage>100,chain=1,if=buff.enrage.up",
+        &settings,
+    )
+    .unwrap();
+    assert_eq!(when, "actions");
+    assert_eq!(action.spell, "bladestorm");
+    assert!(action.chain);
+    assert_eq!(action.raw_interrupt.as_deref(), Some("rage>100"));
+    assert_eq!(
+        format_action(&action, &settings),
+        "bladestorm [recast while channeling]:\n    enrage.up\ninterrupt when:\n    rage > 100"
+    );
+    let (_, action) = process_line("actions+=/bladestorm,chain=0", &settings).unwrap();
+    assert!(!action.chain);
+}
+
+#[test]
+fn trinket_cooldown_tokens_are_humanized() {
+    assert_eq!(
+        humanized("trinket.1.cooldown.ready"),
+        "trinket 1 off cooldown"
+    );
+    assert_eq!(
+        humanized("trinket.stat_increase.cooldown.ready"),
+        "stat_increase trinket off cooldown"
+    );
+    assert_eq!(
+        humanized("trinket.1.cooldown.remains<5"),
+        "trinket 1 cooldown remains less than 5 seconds"
+    );
+}
+
+#[test]
+fn single_part_groups_keep_the_caller_indent() {
+    let single_or = Expr::And(vec![Expr::Or(vec![
+        Expr::Atom(vec!["a".to_string()]),
+        Expr::Atom(vec!["b".to_string()]),
+    ])]);
+    assert_eq!(
+        pretty_format_condition(&single_or, 1, OperatorStyle::Words, Indentation::FourSpaces),
+        "    a\n    OR b"
+    );
+    assert_eq!(
+        pretty_format_condition(
+            &parse_simc_condition("((rage>40))"),
+            1,
+            OperatorStyle::Words,
+            Indentation::FourSpaces
+        ),
+        "    rage > 40"
+    );
+}
+
+#[test]
+fn normalize_rejoins_soft_wrapped_action_lines() {
+    assert_eq!(
+        normalize_apl(
+            "actions+=/rampage,if=rage>=115|\n  buff.enrage.down,\nactions+=/execute,\nif=rage>40"
+        ),
+        "actions+=/rampage,if=rage>=115|buff.enrage.down\nactions+=/execute,if=rage>40"
+    );
+    assert_eq!(
+        normalize_apl("actions+=/bloodthirst,if=buff.enra\nge.down   &  rage<100"),
+        "actions+=/bloodthirst,if=buff.enrage.down & rage<100"
+    );
+}
+
+#[test]
+fn normalize_keeps_settings_and_comments_apart_from_actions() {
+    assert_eq!(
+        normalize_apl("actions+=/y,\nhead=foo\nlevel=80"),
+        "actions+=/y\nhead=foo\nlevel=80"
+    );
+    assert_eq!(
+        normalize_apl("actions+=/y,if=a\nname=foo\n# note\nactions+=/z,if=b&\n# c"),
+        "actions+=/y,if=a\nname=foo\n# note\nactions+=/z,if=b&\n# c"
+    );
+    assert_eq!(
+        normalize_apl("actions+=/y,if=cooldown.avatar.remains>0)\nrage>40"),
+        "actions+=/y,if=cooldown.avatar.remains>0)\nrage>40"
+    );
+}
+
+#[test]
+fn duration_and_max_stack_suffixes_are_humanized() {
+    assert_eq!(
+        humanized("buff.enrage.duration>3"),
+        "enrage total duration more than 3 seconds"
+    );
+    assert_eq!(
+        humanized("buff.enrage.max_stack>2"),
+        "enrage max stacks > 2"
+    );
+    assert_eq!(
+        humanized("buff.enrage.stack>=2"),
+        "enrage at least 2 stacks"
+    );
+    assert_eq!(
+        humanized("buff.avatar.remains<2"),
+        "avatar time left less than 2 seconds"
+    );
+}
+
+#[test]
+fn aura_names_with_apostrophes_stay_whole() {
+    assert_eq!(
+        tokenize_line("buff.it's_a_trap.up&rage>40"),
+        ["buff.it's_a_trap.up", "&", "rage", ">", "40"]
+    );
+    assert_eq!(humanized("buff.it's_a_trap.up"), "it's_a_trap is up");
+    assert_eq!(
+        transform_condition("talent.death's_reach", &Settings::default()),
+        Expr::Atom(vec!["death's_reach".to_string(), "talented".to_string()])
+    );
+}
+
+#[test]
+fn talents_in_arithmetic_render_as_their_rank() {
+    let settings = Settings::default();
+    assert_eq!(
+        transform_condition("talent.x*2>1", &settings),
+        Expr::Atom(vec![
+            "x (talent rank)*2".to_string(),
+            ">".to_string(),
+            "1".to_string()
+        ])
+    );
+    assert_eq!(
+        transform_condition("talent.x&rage>40", &settings),
+        Expr::And(vec![
+            Expr::Atom(vec!["x".to_string(), "talented".to_string()]),
+            Expr::Atom(vec!["rage".to_string(), ">".to_string(), "40".to_string()]),
+        ])
+    );
+}
+
+#[test]
+fn time_to_pct_extracts_the_percentage() {
+    assert_eq!(
+        humanized("target.time_to_pct_20<5"),
+        "time until target reaches 20% less than 5 seconds"
+    );
+    assert_eq!(
+        humanized("target.time_to_pct_35>10"),
+        "time until target reaches 35% more than 10 seconds"
+    );
+    assert_eq!(
+        humanized("target.time_to_pct_35<4"),
+        "time until target reaches 35% less than 4 seconds"
+    );
+}
+
+#[test]
+fn charge_cooldown_suffixes_are_humanized() {
+    assert_eq!(
+        humanized("cooldown.raging_blow.full_recharge_time<2"),
+        "raging_blow time to full charges less than 2 seconds"
+    );
+    assert_eq!(
+        humanized("cooldown.raging_blow.charges_fractional>1.8"),
+        "raging_blow fractional charges > 1.8"
+    );
+}
+
+#[test]
+fn simc_round_trip_drops_display_spacing() {
+    for raw in [
+        "!(buff.enrage.up&rage>=40)|target.health.pct<20",
+        "cooldown.avatar.remains<=gcd.max*2&active_enemies>1",
+    ] {
+        assert_eq!(format_to_simc(&parse_simc_condition(raw)), raw);
+    }
+    let settings = Settings::default();
+    let (when, action) = process_line(
+        "actions+=/execute,if=rage >= 40 & buff.sudden_death.up",
+        &settings,
+    )
+    .unwrap();
+    assert_eq!(
+        format_action_simc(&when, &action),
+        "actions+=/execute,if=rage>=40&buff.sudden_death.up"
+    );
+}
+
+#[test]
+fn movement_tokens_are_humanized() {
+    assert_eq!(humanized("movement.distance>10"), "distance moved > 10");
+    assert_eq!(humanized("moving"), "while moving");
+    assert_eq!(humanized("moving=0"), "while not moving");
+    assert_eq!(humanized("!moving"), "while not moving");
+}
+
+#[test]
+fn malformed_lines_fail_inline_without_hiding_the_rest() {
+    let deep = format!(
+        "{}rage>40{}",
+        "(".repeat(MAX_CONDITION_DEPTH + 1),
+        ")".repeat(MAX_CONDITION_DEPTH + 1)
+    );
+    let apl = format!(
+        "actions=charge\nactions+=/rampage,if={}\nactions+=/execute,if=(rage>40\nactions+=/slam,if=rage>40)\nactions+=/whirlwind",
+        deep
+    );
+    let groups = process_apl_grouped(&apl, &Settings::default());
+    assert_eq!(groups.keys().collect::<Vec<_>>(), ["actions"]);
+
+    let actions = &groups["actions"];
+    let errors: Vec<Option<&str>> = actions.iter().map(|a| a.error.as_deref()).collect();
+    assert_eq!(
+        errors,
+        [
+            None,
+            Some("condition nests more than 32 levels deep"),
+            Some("1 unclosed `(` in condition"),
+            Some("unmatched `)` in condition"),
+            None,
+        ]
+    );
+    assert_eq!(actions[1].spell, "rampage");
+    assert!(actions[1].condition.is_none());
+    assert_eq!(actions[4].spell, "whirlwind");
+}
+
+#[test]
+fn long_negation_chains_fail_inline_instead_of_recursing() {
+    let apl = format!("actions=rampage,if={}rage>40", "!".repeat(5000));
+    let groups = process_apl_grouped(&apl, &Settings::default());
+    let actions = &groups["actions"];
+    assert_eq!(
+        actions[0].error.as_deref(),
+        Some("condition nests more than 32 levels deep")
+    );
+    assert!(format_group_simc("actions", actions).ends_with("!rage>40"));
+    assert!(gear_dependencies(&groups).is_empty());
+    assert!(check_nesting(&format!("{}rage>40", "!".repeat(MAX_CONDITION_DEPTH))).is_ok());
+}
+
+#[test]
+fn debuff_stacks_mention_the_target() {
+    assert_eq!(
+        humanized("debuff.deep_wounds.stack=2"),
+        "deep_wounds at 2 stacks on target"
+    );
+    assert_eq!(
+        humanized("debuff.executioners_precision.stack<2"),
+        "executioners_precision fewer than 2 stacks on target"
+    );
+    assert_eq!(humanized("buff.enrage.stack=2"), "enrage at 2 stacks");
+}
+
+#[test]
+fn markdown_export_lists_each_group_under_a_heading() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "actions=execute,if=rage>40\nactions+=/slam\nactions.aoe=whirlwind",
+        &settings,
+    );
+    assert_eq!(
+        format_groups_markdown(&groups, &settings),
+        "### actions\n\n1. `execute`\n   ```\n   rage > 40\n   ```\n2. `slam`\n\n### aoe\n\n1. `whirlwind`"
+    );
+}
+
+#[test]
+fn raid_buffs_are_labelled_as_external() {
+    assert_eq!(
+        humanized("buff.bloodlust.up"),
+        "Bloodlust active (raid buff)"
+    );
+    assert_eq!(
+        humanized("buff.power_infusion.up"),
+        "Power Infusion active (raid buff)"
+    );
+    assert_eq!(humanized("buff.enrage.up"), "enrage is up");
+}
+
+#[test]
+fn react_is_distinguished_from_up() {
+    assert_eq!(
+        humanized("buff.sudden_death.react"),
+        "sudden_death is up (reactable)"
+    );
+    assert_eq!(humanized("buff.sudden_death.up"), "sudden_death is up");
+}
+
+#[test]
+fn csv_export_escapes_commas_and_quotes() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "actions=execute,if=rage>40\nactions+=/slam,if=a,b\"c\"\nactions.aoe=whirlwind",
+        &settings,
+    );
+    assert_eq!(
+        format_groups_csv(&groups, &settings),
+        "list,priority,spell,raw_condition,formatted_condition\nactions,1,execute,rage>40,rage > 40\nactions,2,slam,\"a,b\"\"c\"\"\",\"a,b\"\"c\"\"\"\naoe,1,whirlwind,,"
+    );
+    assert_eq!(csv_field("plain"), "plain");
+    assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+}
+
+#[test]
+fn continuations_skip_comments_and_blank_lines() {
+    assert_eq!(
+        join_continuation_lines("actions=x,if=a&\n# c\n\nb\nactions+=/y"),
+        "# c\n\nactions=x,if=a&b\nactions+=/y"
+    );
+    assert_eq!(
+        join_continuation_lines("actions=x,if=a|\n# trailing"),
+        "actions=x,if=a|\n# trailing"
+    );
+}
+
+#[test]
+fn raid_event_adds_are_humanized() {
+    assert_eq!(
+        humanized("raid_event.adds.in>20"),
+        "adds arrive in more than 20 seconds"
+    );
+    assert_eq!(
+        humanized("raid_event.adds.remains>5"),
+        "adds last for more than 5 seconds"
+    );
+}
+
+#[test]
+fn tree_view_draws_one_node_per_line() {
+    assert_eq!(
+        format_condition_tree(&parse_simc_condition("a&(b|!c)"), OperatorStyle::Words),
+        "AND\n├─ a\n└─ OR\n   ├─ b\n   └─ NOT\n      └─ c"
+    );
+    assert_eq!(
+        format_condition_tree(&parse_simc_condition("a|b"), OperatorStyle::Symbols),
+        "∨\n├─ a\n└─ b"
+    );
+}
+
+#[test]
+fn inline_comments_trail_the_formatted_action() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "actions=mortal_strike  # generator\nactions+=/execute,if=rage>40 # spender",
+        &settings,
+    );
+    let actions = &groups["actions"];
+    assert_eq!(actions[0].spell, "mortal_strike");
+    assert_eq!(actions[0].annotation.as_deref(), Some("generator"));
+    assert_eq!(
+        format_group(actions, &action_numbers(actions), &settings),
+        "(1) mortal_strike  # generator\n\n(2) execute:\n    rage > 40  # spender"
+    );
+}
+
+#[test]
+fn pandemic_window_matches_named_durations_of_the_same_aura() {
+    let settings = Settings {
+        humanize: true,
+        ..Settings::default()
+    };
+    let humanized = |raw: &str| {
+        let Expr::Atom(tokens) = parse_simc_condition(raw) else {
+            panic!("{raw} is not a single atom");
+        };
+        humanize_atom(&tokens, &settings).map(|words| words.join(" "))
+    };
+    assert_eq!(
+        humanized("dot.rend.remains<duration*0.3").as_deref(),
+        Some("rend time left less than 30% of its duration (pandemic refresh window)")
+    );
+    assert_eq!(
+        humanized("dot.deep_wounds.remains<=0.3*dot.deep_wounds.duration").as_deref(),
+        Some("deep_wounds time left at most 30% of deep_wounds total duration (pandemic refresh window)")
+    );
+    assert!(!humanized("dot.rend.remains<dot.deep_wounds.duration*0.3")
+        .unwrap()
+        .contains("pandemic"));
+}
+
+#[test]
+fn group_by_spell_collects_every_list_using_a_spell() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "# opener\nactions=mortal_strike,if=rage>40\nactions+=/execute\nactions.aoe=whirlwind\nactions.aoe+=/mortal_strike",
+        &Settings {
+            comment_mode: CommentMode::Inline,
+            ..settings
+        },
+    );
+    let by_spell = group_by_spell(&groups);
+    assert_eq!(
+        by_spell.keys().collect::<Vec<_>>(),
+        ["execute", "mortal_strike", "whirlwind"]
+    );
+    let usages: Vec<(&str, usize)> = by_spell["mortal_strike"]
+        .iter()
+        .map(|(list, index, _)| (list.as_str(), *index))
+        .collect();
+    assert_eq!(usages, [("actions", 1), ("aoe", 1)]);
+}
+
+#[test]
+fn gcd_comparisons_stay_in_time_units() {
+    assert_eq!(
+        humanized("buff.enrage.remains<gcd.max"),
+        "enrage time left less than GCD"
+    );
+    assert_eq!(
+        humanized("buff.enrage.remains<gcd.max*2"),
+        "enrage time left less than GCD * 2"
+    );
+    assert_eq!(
+        humanized("buff.enrage.remains<1.5"),
+        "enrage time left less than 1.5 seconds"
+    );
+}
+
+#[test]
+fn unknown_spells_are_flagged() {
+    assert!(is_known_spell("mortal_strike"));
+    assert!(is_known_spell("mortal_strike,if=rage>40"));
+    assert!(!is_known_spell("morta_strike"));
+}
+
+#[test]
+fn use_item_shows_the_item_name_or_slot() {
+    let settings = Settings::default();
+    let (_, action) = process_line(
+        "actions+=/use_item,name=treacherous_transmitter,if=cooldown.avatar.remains<5",
+        &settings,
+    )
+    .unwrap();
+    assert_eq!(
+        format_action(&action, &settings),
+        "use item: treacherous_transmitter:\n    cooldown.avatar.remains < 5"
+    );
+    assert_eq!(
+        display_spell("use_item,slot=trinket1"),
+        "use item: trinket1"
+    );
+}
+
+#[test]
+fn outline_export_indents_conditions_under_spells() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "actions=execute,if=rage>40|buff.sudden_death.up\nactions.aoe=whirlwind",
+        &settings,
+    );
+    assert_eq!(
+        format_groups_outline(&groups, &settings),
+        "actions\n\texecute\n\t\trage > 40\n\t\tOR sudden_death.up\n\naoe\n\twhirlwind"
+    );
+}
+
+#[test]
+fn constant_conditions_are_folded_over_the_whole_tree() {
+    let constant = |raw: &str| constant_value(&parse_simc_condition(raw));
+    assert_eq!(constant("talent.x|!talent.x"), Some(true));
+    assert_eq!(constant("talent.x&!talent.x"), Some(false));
+    assert_eq!(constant("!(a|!a)"), Some(false));
+    assert_eq!(constant("(a|!a)|b"), Some(true));
+    assert_eq!(constant("(a&!a)&b"), Some(false));
+    assert_eq!(constant("(a|!a)&(b&!b|c&!c)"), Some(false));
+    assert_eq!(constant("(a|!a)&b"), None);
+    assert_eq!(constant("!((a|!a)&b)"), None);
+    assert_eq!(constant("(a&!a)|b"), None);
+    assert_eq!(constant("a&b"), None);
+}
+
+#[test]
+fn target_distance_is_humanized() {
+    assert_eq!(
+        humanized("target.distance<=5"),
+        "distance to target at most 5"
+    );
+    assert_eq!(
+        humanized("target.distance>8"),
+        "distance to target more than 8"
+    );
+}
+
+#[test]
+fn list_entry_conditions_come_from_the_calling_action() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "actions=run_action_list,name=aoe,if=active_enemies>2\nactions+=/call_action_list,name=cds\nactions.aoe=whirlwind\nactions.cds=avatar",
+        &settings,
+    );
+    let entries = list_entry_conditions(&groups);
+    assert_eq!(
+        entries["aoe"],
+        [(
+            "actions".to_string(),
+            Some(parse_simc_condition("active_enemies>2"))
+        )]
+    );
+    assert_eq!(entries["cds"], [("actions".to_string(), None)]);
+    assert!(!entries.contains_key("actions"));
+}
+
+#[test]
+fn list_entry_conditions_and_every_calling_list() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "actions=call_action_list,name=cds,if=rage>=40\nactions.cds=call_action_list,name=trinkets,if=target.time_to_die>20\nactions.cds+=/call_action_list,name=cds\nactions.trinkets=use_items\nactions.trinkets+=/call_action_list,name=cds",
+        &settings,
+    );
+    let entries = list_entry_conditions(&groups);
+    assert_eq!(
+        entries["trinkets"],
+        [(
+            "actions → cds".to_string(),
+            Some(Expr::And(vec![
+                parse_simc_condition("rage>=40"),
+                parse_simc_condition("target.time_to_die>20"),
+            ]))
+        )]
+    );
+    assert_eq!(
+        entries["cds"],
+        [(
+            "actions".to_string(),
+            Some(parse_simc_condition("rage>=40"))
+        )]
+    );
+}
+
+#[test]
+fn percentage_suffixes_win_over_shorter_ones() {
+    assert_eq!(humanized("buff.x.stack_pct>50"), "x stack % > 50");
+    assert_eq!(humanized("buff.x.value_pct<10"), "x value % < 10");
+    assert_eq!(humanized("buff.x.value_pct>50"), "x value % > 50");
+    assert_eq!(humanized("buff.x.stack>1"), "x more than 1 stacks");
+}
+
+#[test]
+fn resource_suffixes_apply_to_every_resource() {
+    assert_eq!(humanized("rage.deficit<10"), "rage missing from max < 10");
+    assert_eq!(
+        humanized("energy.deficit>20"),
+        "energy missing from max > 20"
+    );
+    assert_eq!(
+        humanized("energy.deficit<20"),
+        "energy missing from max < 20"
+    );
+    assert_eq!(
+        humanized("focus.time_to_max<2"),
+        "time until focus is full less than 2 seconds"
+    );
+    assert_eq!(
+        humanized("fury.time_to_max<1"),
+        "time until fury is full less than 1 second"
+    );
+}
+
+#[test]
+fn action_keys_follow_the_action_not_its_position() {
+    let settings = Settings::default();
+    let before = process_apl_grouped(
+        "actions=charge\nactions+=/execute,if=rage>40\nactions+=/execute",
+        &settings,
+    );
+    let after = process_apl_grouped(
+        "actions=execute\nactions+=/charge\nactions+=/execute,if=rage>40",
+        &settings,
+    );
+    let key = action_key("Profile 1", "actions", &before["actions"][1]);
+    assert_eq!(
+        key,
+        action_key("Profile 1", "actions", &after["actions"][2])
+    );
+    assert_ne!(
+        key,
+        action_key("Profile 1", "actions", &after["actions"][0])
+    );
+    assert_ne!(
+        key,
+        action_key("Profile 2", "actions", &before["actions"][1])
+    );
+}
+
+#[test]
+fn off_gcd_weaving_tokens_are_humanized() {
+    assert_eq!(
+        humanized("prev_off_gcd.avatar"),
+        "last off-GCD cast was avatar"
+    );
+    assert_eq!(
+        humanized("gcd.remains<0.5"),
+        "time until GCD is ready less than 0.5 seconds"
+    );
+}
+
+#[test]
+fn tick_time_is_time_typed() {
+    assert_eq!(
+        humanized("dot.rend.remains<=dot.rend.tick_time"),
+        "rend time left at most rend tick interval"
+    );
+}
+
+#[test]
+fn share_link_percent_encodes_the_apl() {
+    assert_eq!(
+        share_link("https://example.com/", "actions+=/execute,if=rage>=40 # né"),
+        "https://example.com/#apl=actions%2B%3D%2Fexecute%2Cif%3Drage%3E%3D40%20%23%20n%C3%A9"
+    );
+}
+
+#[test]
+fn cooldown_duration_and_remains_are_worded_apart() {
+    assert_eq!(
+        humanized("cooldown.avatar.duration>60"),
+        "avatar cooldown length more than 60 seconds"
+    );
+    assert_eq!(
+        humanized("cooldown.avatar.remains<5"),
+        "avatar cooldown remaining less than 5 seconds"
+    );
+    assert_eq!(
+        humanized("buff.avatar.duration>5"),
+        "avatar total duration more than 5 seconds"
+    );
+}
+
+#[test]
+fn operator_styles_change_only_the_joiners() {
+    let expr = parse_simc_condition("!a&(b|c)");
+    let render = |style| pretty_format_condition(&expr, 0, style, Indentation::TwoSpaces);
+    assert_eq!(render(OperatorStyle::Words), "NOT a AND (\n  b\n  OR c\n)");
+    assert_eq!(render(OperatorStyle::Symbols), "¬a ∧ (\n  b\n  ∨ c\n)");
+    assert_eq!(render(OperatorStyle::Simc), "!a & (\n  b\n  | c\n)");
+}
+
+#[test]
+fn stat_tokens_are_humanized() {
+    assert_eq!(humanized("crit_pct>30"), "crit % > 30");
+    assert_eq!(humanized("haste_pct>20"), "haste % > 20");
+    assert_eq!(humanized("mastery_value>1"), "mastery > 1");
+}
+
+#[test]
+fn shared_condition_runs_group_consecutive_identical_conditions() {
+    let groups = process_apl_grouped(
+        "actions=avatar,if=buff.recklessness.up\nactions+=/odyns_fury,if=buff.recklessness.up\nactions+=/thunderous_roar,if=buff.recklessness.up\nactions+=/rampage\nactions+=/bloodthirst\nactions+=/raging_blow,if=rage<100\nactions+=/whirlwind,if=rage<100",
+        &Settings::default(),
+    );
+    let actions = &groups["actions"];
+    assert_eq!(shared_condition_runs(actions), [(0, 3), (5, 2)]);
+}
+
+#[test]
+fn cycle_modifiers_become_a_targeting_hint() {
+    let settings = Settings::default();
+    let (_, action) = process_line(
+        "actions+=/rend,cycle_targets=1,max_cycle_targets=3,if=refreshable",
+        &settings,
+    )
+    .unwrap();
+    assert_eq!(
+        format_action(&action, &settings),
+        "rend [cycles up to 3 targets]:\n    refreshable"
+    );
+    assert_eq!(
+        targeting_hint("rend,cycle_targets=1").as_deref(),
+        Some("cycles through targets")
+    );
+    assert_eq!(targeting_hint("rend,cycle_targets=0"), None);
+}
+
+#[test]
+fn move_focus_clamps_a_stale_position() {
+    assert_eq!(
+        move_focus(Some((3, 0)), &[2], &Key::ArrowDown),
+        Some((0, 1))
+    );
+    assert_eq!(
+        move_focus(Some((1, 7)), &[4, 3], &Key::ArrowUp),
+        Some((1, 2))
+    );
+    assert_eq!(
+        move_focus(Some((5, 5)), &[1], &Key::ArrowLeft),
+        Some((0, 0))
+    );
+}
+
+#[test]
+fn move_focus_steps_between_groups() {
+    assert_eq!(move_focus(None, &[2, 2], &Key::ArrowDown), Some((0, 0)));
+    assert_eq!(
+        move_focus(Some((0, 1)), &[2, 1], &Key::ArrowRight),
+        Some((1, 0))
+    );
+    assert_eq!(move_focus(Some((0, 0)), &[], &Key::ArrowDown), None);
+    assert_eq!(move_focus(None, &[2, 2], &Key::Tab), None);
+    assert_eq!(move_focus(None, &[2, 2], &Key::Enter), None);
+}
+
+#[test]
+fn action_cast_times_are_time_typed() {
+    assert_eq!(
+        humanized("buff.x.remains>action.bladestorm.execute_time"),
+        "x time left more than bladestorm cast time"
+    );
+    assert_eq!(
+        humanized("action.slam.cast_time<1"),
+        "slam cast time less than 1 second"
+    );
+}
+
+#[test]
+fn explain_narrates_the_condition() {
+    let settings = Settings {
+        humanize: true,
+        ..Settings::default()
+    };
+    let (_, action) = process_line(
+        "actions+=/mortal_strike,if=dot.deep_wounds.remains<2&rage>=40",
+        &settings,
+    )
+    .unwrap();
+    assert_eq!(
+        explain_action(&action),
+        "Cast Mortal Strike when deep_wounds time left less than 2 seconds and rage is at least 40."
+    );
+    let (_, action) = process_line("actions+=/bladestorm", &settings).unwrap();
+    assert_eq!(
+        explain_action(&action),
+        "Cast Bladestorm whenever it is available."
+    );
+}
+
+#[test]
+fn profiles_split_on_headers_and_repeated_precombat_lists() {
+    let profiles = split_profiles(
+        "warrior=\"Arms\"\nactions=mortal_strike\nactions.precombat=battle_shout\nactions=execute\nwarrior=\"Fury\"\nactions=rampage",
+    );
+    let names: Vec<&str> = profiles.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["Arms", "Profile 2", "Fury"]);
+    assert_eq!(
+        profiles[1].1,
+        "actions.precombat=battle_shout\nactions=execute"
+    );
+    assert_eq!(profiles[2].1, "warrior=\"Fury\"\nactions=rampage");
+}
+
+#[test]
+fn simplify_removes_duplicate_and_absorbed_operands() {
+    assert_eq!(
+        simplify(parse_simc_condition("a|(a&b)")),
+        parse_simc_condition("a")
+    );
+    assert_eq!(
+        simplify(parse_simc_condition("a&a&(a|b)&c")),
+        parse_simc_condition("a&c")
+    );
+    assert_eq!(
+        simplify(parse_simc_condition("rage>40|rage>=40")),
+        parse_simc_condition("rage>40|rage>=40")
+    );
+}
+
+#[test]
+fn negated_talents_parse_into_not_nodes() {
+    let settings = Settings::default();
+    let talented = |name: &str| Expr::Atom(vec![name.to_string(), "talented".to_string()]);
+    let not = |expr: Expr| Expr::Not(Box::new(expr));
+    assert_eq!(
+        transform_condition("!talent.bladestorm", &settings),
+        not(talented("bladestorm"))
+    );
+    assert_eq!(
+        transform_condition("!talent.bladestorm&talent.ravager", &settings),
+        Expr::And(vec![not(talented("bladestorm")), talented("ravager")])
+    );
+    assert_eq!(
+        transform_condition("!(talent.bladestorm|talent.ravager)", &settings),
+        not(Expr::Or(vec![talented("bladestorm"), talented("ravager")]))
+    );
+}
+
+#[test]
+fn remains_on_both_sides_are_both_time_typed() {
+    assert_eq!(
+        humanized("buff.a.remains<buff.b.remains"),
+        "a time left less than b time left"
+    );
+    assert_eq!(
+        humanized("cooldown.avatar.remains>buff.enrage.remains"),
+        "avatar cooldown remaining more than enrage time left"
+    );
+}
+
+#[test]
+fn dot_export_labels_operators_and_escapes_atoms() {
+    assert_eq!(
+        expr_to_dot(&parse_simc_condition("a&!\"b\"")),
+        "digraph condition {\n  n0 [label=\"AND\", shape=ellipse];\n  n1 [label=\"a\", shape=box];\n  n0 -> n1;\n  n2 [label=\"NOT\", shape=ellipse];\n  n3 [label=\"\\\"b\\\"\", shape=box];\n  n2 -> n3;\n  n0 -> n2;\n}"
+    );
+}
+
+#[test]
+fn comment_modes_drop_annotate_or_inline_comments() {
+    let apl = "# opener\nactions=charge,if=rage<20";
+    let with_mode = |comment_mode| {
+        process_apl_grouped(
+            apl,
+            &Settings {
+                comment_mode,
+                ..Settings::default()
+            },
+        )
+        .remove("actions")
+        .unwrap_or_default()
+    };
+    let condition = Some(parse_simc_condition("rage<20"));
+
+    let ignored = with_mode(CommentMode::Ignore);
+    assert_eq!(ignored.len(), 1);
+    assert_eq!(ignored[0].annotation, None);
+    assert_eq!(ignored[0].condition, condition);
+
+    let annotated = with_mode(CommentMode::Annotate);
+    assert_eq!(annotated.len(), 1);
+    assert_eq!(annotated[0].annotation.as_deref(), Some("opener"));
+    assert_eq!(annotated[0].condition, condition);
+
+    let inline = with_mode(CommentMode::Inline);
+    assert_eq!(inline.len(), 2);
+    assert!(inline[0].comment);
+    assert_eq!(inline[0].annotation.as_deref(), Some("opener"));
+    assert_eq!(inline[1].annotation, None);
+    assert_eq!(inline[1].condition, condition);
+}
+
+#[test]
+fn hero_trees_are_title_cased() {
+    assert_eq!(humanized("hero_tree.slayer"), "Slayer hero tree");
+    assert_eq!(
+        humanized("hero_tree.mountain_thane"),
+        "Mountain Thane hero tree"
+    );
+}
+
+#[test]
+fn gear_dependencies_collect_set_bonuses_and_items() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "actions=avatar,if=set_bonus.tww2_4pc&equipped.treacherous_transmitter\nactions.aoe=whirlwind,interrupt_if=set_bonus.tww2_2pc\nactions+=/execute,if=rage>40",
+        &settings,
+    );
+    assert_eq!(
+        gear_dependencies(&groups).into_iter().collect::<Vec<_>>(),
+        [
+            "equipped.treacherous_transmitter",
+            "set_bonus.tww2_2pc",
+            "set_bonus.tww2_4pc"
+        ]
+    );
+}
+
+#[test]
+fn continuous_numbering_carries_across_groups() {
+    let apl =
+        "actions=charge\n# note\nactions+=/execute\nactions.aoe=whirlwind\nactions.aoe+=/cleave";
+    let mut settings = Settings {
+        comment_mode: CommentMode::Inline,
+        ..Settings::default()
+    };
+    let groups = process_apl_grouped(apl, &settings);
+    assert_eq!(display_numbers(&groups, &settings)["aoe"], [1, 2]);
+    settings.continuous_numbering = true;
+    let numbers = display_numbers(&groups, &settings);
+    assert_eq!(numbers["actions"], [1, 1, 2]);
+    assert_eq!(numbers["aoe"], [3, 4]);
+}
+
+#[test]
+fn disabled_talents_lower_to_negation() {
+    let settings = Settings::default();
+    let talented = |name: &str| Expr::Atom(vec![name.to_string(), "talented".to_string()]);
+    assert_eq!(
+        transform_condition("talent.bladestorm.enabled", &settings),
+        talented("bladestorm")
+    );
+    assert_eq!(
+        transform_condition("talent.bladestorm.disabled", &settings),
+        Expr::Not(Box::new(talented("bladestorm")))
+    );
+    assert_eq!(
+        transform_condition("!talent.bladestorm.disabled", &settings),
+        talented("bladestorm")
+    );
+    assert_eq!(
+        transform_condition("!(talent.bladestorm.disabled)&rage>40", &settings),
+        Expr::And(vec![
+            talented("bladestorm"),
+            Expr::Atom(vec!["rage".to_string(), ">".to_string(), "40".to_string()]),
+        ])
+    );
+}
+
+#[test]
+fn diff_marks_added_changed_and_removed_actions() {
+    let settings = Settings::default();
+    let previous = process_apl_grouped(
+        "actions=avatar\nactions+=/execute,if=rage>20\nactions+=/bloodthirst",
+        &settings,
+    );
+    let current = process_apl_grouped(
+        "actions=avatar\nactions+=/execute,if=rage>40\nactions+=/slam",
+        &settings,
+    );
+    let (changes, removed) = diff_actions(&previous["actions"], &current["actions"]);
+    assert_eq!(
+        changes,
+        [
+            ChangeKind::Unchanged,
+            ChangeKind::Changed,
+            ChangeKind::Added
+        ]
+    );
+    assert_eq!(removed, ["bloodthirst"]);
+}
+
+#[test]
+fn raid_event_movement_and_stun_are_humanized() {
+    assert_eq!(
+        humanized("raid_event.movement.in<5"),
+        "forced movement in less than 5 seconds"
+    );
+    assert_eq!(humanized("raid_event.stun.exists"), "incoming stun");
+}
+
+#[test]
+fn actions_below_an_unconditional_one_are_unreachable() {
+    let settings = Settings::default();
+    let groups = process_apl_grouped(
+        "actions=avatar,if=rage>40\nactions+=/run_action_list,name=aoe,if=active_enemies>2\nactions+=/execute\nactions+=/slam\nactions+=/whirlwind",
+        &settings,
+    );
+    assert_eq!(
+        unreachable_after(&groups["actions"]),
+        [
+            None,
+            None,
+            None,
+            Some("execute".to_string()),
+            Some("execute".to_string())
+        ]
+    );
+}
+
+#[test]
+fn stack_comparisons_against_variables_compose() {
+    assert_eq!(
+        humanized("buff.x.stack>=variable.threshold"),
+        "x at least threshold stacks"
+    );
+    assert_eq!(
+        humanized("buff.x.stack<variable.cap-1"),
+        "x fewer than cap - 1 stacks"
+    );
+}
+
+#[test]
+fn copied_text_follows_the_card_numbering_and_layout() {
+    let apl = "actions=mortal_strike,if=rage>40&buff.enrage.up\nactions+=/execute,if=rage>40&buff.enrage.up\nactions.aoe=whirlwind\nactions.aoe+=/mortal_strike";
+    let mut settings = Settings {
+        continuous_numbering: true,
+        merge_shared_conditions: true,
+        ..Settings::default()
+    };
+    let groups = process_apl_grouped(apl, &settings);
+    assert_eq!(
+        format_groups(&groups, &settings),
+        "actions\n\nthe next 2 actions share:\n    rage > 40 AND enrage.up\n\n(1) mortal_strike\n\n(2) execute\n\n\naoe\n\n(3) whirlwind\n\n(4) mortal_strike"
+    );
+
+    settings.tree_view = true;
+    settings.merge_shared_conditions = false;
+    assert_eq!(
+        format_group(&groups["actions"][..1], &[1], &settings),
+        "(1) mortal_strike:\n    AND\n    ├─ rage > 40\n    └─ enrage.up"
+    );
+
+    settings.tree_view = false;
+    settings.group_by_spell = true;
+    assert!(format_groups(&groups, &settings).starts_with(
+        "execute\n\nactions\n(2) execute:\n    rage > 40 AND enrage.up\n\n\nmortal_strike\n\nactions\n(1) mortal_strike:"
+    ));
+    assert!(format_groups(&groups, &settings).contains("aoe\n(4) mortal_strike"));
+}
+
+#[test]
+fn glossary_covers_every_humanization_rule() {
+    let glossary = glossary_entries();
+    let patterns: Vec<&str> = glossary
+        .iter()
+        .map(|(pattern, _)| pattern.as_str())
+        .collect();
+    for (re, _) in HUMANIZE_RULES.iter() {
+        assert!(patterns.contains(&readable_pattern(re.as_str()).0.as_str()));
+    }
+    for rule in TOKEN_RULES {
+        for example in (rule.examples)() {
+            assert!(patterns.contains(&example.as_str()), "{example} missing");
+        }
+    }
+
+    let settings = Settings {
+        humanize: true,
+        ..Settings::default()
+    };
+    for rule in ATOM_RULES {
+        let Expr::Atom(tokens) = parse_simc_condition(rule.example) else {
+            panic!("{} is not a single atom", rule.example);
+        };
+        let own: Vec<&str> = tokens.iter().map(String::as_str).collect();
+        let rendering = (rule.humanize)(&own, &settings).expect(rule.example);
+        assert_eq!(humanize_atom(&tokens, &settings), Some(rendering.clone()));
+        assert!(glossary.contains(&(own.join(" "), rendering.join(" "))));
+    }
+    assert!(glossary.contains(&(
+        "buff.enrage.stack < 3".to_string(),
+        "enrage fewer than 3 stacks".to_string()
+    )));
+    assert!(glossary.contains(&(
+        "hero_tree.slayer".to_string(),
+        "Slayer hero tree".to_string()
+    )));
+}
+
+#[test]
+fn trailing_comments_split_only_after_the_last_modifier() {
+    assert_eq!(
+        split_trailing_comment("mortal_strike,if=rage>40 # generator"),
+        ("mortal_strike,if=rage>40", Some("generator".to_string()))
+    );
+    assert_eq!(
+        split_trailing_comment("variable,name=note #1,value=2,if=rage>40"),
+        ("variable,name=note #1,value=2,if=rage>40", None)
+    );
+    assert_eq!(
+        split_trailing_comment("execute,if=(rage>40 #x)"),
+        ("execute,if=(rage>40 #x)", None)
+    );
+    assert_eq!(split_trailing_comment("slam #"), ("slam", None));
+}
+
+#[test]
+fn custom_rules_take_priority_over_built_in_phrases() {
+    let settings = Settings {
+        humanize: true,
+        custom_rules: "buff\\.enrage\\.up => enraged\nmoving => running\n(unclosed => x"
+            .to_string()
+            .into(),
+        ..Settings::default()
+    };
+    assert_eq!(settings.custom_rules.rules.len(), 2);
+    assert_eq!(settings.custom_rules.errors.len(), 1);
+    assert!(settings.custom_rules.errors[0].starts_with("line 3:"));
+
+    let atoms = |raw: &str| match transform_condition(raw, &settings) {
+        Expr::Atom(tokens) => tokens.join(" "),
+        other => panic!("{other:?}"),
+    };
+    assert_eq!(atoms("buff.enrage.up"), "enraged");
+    assert_eq!(atoms("buff.recklessness.up"), "recklessness is up");
+    assert_eq!(atoms("moving=0"), "running = 0");
+
+    let restored: CustomRules =
+        serde_json::from_str(&serde_json::to_string(&settings.custom_rules).unwrap()).unwrap();
+    assert_eq!(restored, settings.custom_rules);
+    assert_eq!(restored.rules.len(), 2);
+}
+
+#[test]
+fn in_flight_tokens_are_humanized() {
+    assert_eq!(humanized("action.charge.in_flight"), "charge is mid-flight");
+    assert_eq!(
+        humanized("action.charge.in_flight_to_target"),
+        "charge is mid-flight to target"
+    );
+}
+
+#[test]
+fn bundles_round_trip_input_and_settings() {
+    let settings = Settings {
+        humanize: true,
+        operator_style: OperatorStyle::Symbols,
+        custom_rules: "buff\\.x\\.up => x".to_string().into(),
+        ..Settings::default()
+    };
+    let bundle = parse_bundle(&bundle_json("actions=execute", &settings)).unwrap();
+    assert_eq!(bundle.input, "actions=execute");
+    assert_eq!(bundle.settings, settings);
+    assert_eq!(bundle.settings.custom_rules.rules.len(), 1);
+    assert!(parse_bundle("not json").is_err());
+}
+
+#[test]
+fn health_and_incoming_damage_are_humanized() {
+    assert_eq!(humanized("health.pct<40"), "my HP% < 40");
+    assert_eq!(
+        humanized("incoming_damage_5s>100000"),
+        "damage taken over last 5 seconds > 100000"
+    );
+    assert_eq!(humanized("target.health.pct<20"), "target.health.pct < 20");
+}
+
+#[test]
+fn glossary_entries_show_no_raw_regex_syntax() {
+    for (pattern, rendering) in glossary_entries() {
+        for text in [&pattern, &rendering] {
+            assert!(
+                !text.contains('\\') && !text.contains("[") && !text.contains("(?"),
+                "raw regex syntax in glossary entry {pattern:?} => {rendering:?}"
+            );
+        }
+    }
+    assert!(glossary_entries().contains(&(
+        "(main|off)_hand.Nh".to_string(),
+        "N-handed main-hand weapon".to_string()
+    )));
+}